@@ -4,10 +4,82 @@ CREATE TABLE IF NOT EXISTS iss_fetch_log (
     id BIGSERIAL PRIMARY KEY,
     fetched_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
     source_url TEXT NOT NULL,
-    payload JSONB NOT NULL
+    payload JSONB NOT NULL,
+    latitude DOUBLE PRECISION,
+    longitude DOUBLE PRECISION,
+    altitude DOUBLE PRECISION,
+    velocity DOUBLE PRECISION,
+    visibility TEXT,
+    footprint DOUBLE PRECISION,
+    observed_at TIMESTAMPTZ,
+    norad_id INTEGER NOT NULL DEFAULT 25544,
+    anomalies TEXT[] NOT NULL DEFAULT '{}',
+    content_hash TEXT
 );
 CREATE INDEX IF NOT EXISTS idx_iss_fetch_log_fetched_at ON iss_fetch_log (fetched_at DESC);
 CREATE INDEX IF NOT EXISTS idx_iss_fetch_log_source_time ON iss_fetch_log (source_url, fetched_at DESC);
+CREATE INDEX IF NOT EXISTS idx_iss_fetch_log_norad_time ON iss_fetch_log (norad_id, fetched_at DESC);
+CREATE INDEX IF NOT EXISTS idx_iss_fetch_log_norad_position ON iss_fetch_log (norad_id, latitude, longitude)
+    WHERE cardinality(anomalies) = 0;
+CREATE INDEX IF NOT EXISTS idx_iss_fetch_log_anomalies ON iss_fetch_log (norad_id, id DESC)
+    WHERE cardinality(anomalies) > 0;
+
+CREATE TABLE IF NOT EXISTS iss_rollup_hourly (
+    norad_id INTEGER NOT NULL,
+    bucket TIMESTAMPTZ NOT NULL,
+    sample_count BIGINT NOT NULL,
+    avg_velocity DOUBLE PRECISION,
+    min_altitude DOUBLE PRECISION,
+    max_altitude DOUBLE PRECISION,
+    orbit_count BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (norad_id, bucket)
+);
+
+CREATE TABLE IF NOT EXISTS iss_rollup_daily (
+    norad_id INTEGER NOT NULL,
+    bucket TIMESTAMPTZ NOT NULL,
+    sample_count BIGINT NOT NULL,
+    avg_velocity DOUBLE PRECISION,
+    min_altitude DOUBLE PRECISION,
+    max_altitude DOUBLE PRECISION,
+    orbit_count BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (norad_id, bucket)
+);
+
+CREATE TABLE IF NOT EXISTS iss_geofences (
+    id BIGSERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    shape JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS iss_geofence_events (
+    id BIGSERIAL PRIMARY KEY,
+    geofence_id BIGINT NOT NULL REFERENCES iss_geofences(id) ON DELETE CASCADE,
+    norad_id INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    entry_id BIGINT NOT NULL,
+    at TIMESTAMPTZ NOT NULL,
+    latitude DOUBLE PRECISION NOT NULL,
+    longitude DOUBLE PRECISION NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_iss_geofence_events_fence_at ON iss_geofence_events (geofence_id, at DESC);
+CREATE INDEX IF NOT EXISTS idx_iss_geofence_events_norad_fence ON iss_geofence_events (norad_id, geofence_id, id DESC);
+
+CREATE TABLE IF NOT EXISTS iss_orbit_events (
+    id BIGSERIAL PRIMARY KEY,
+    norad_id INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    at TIMESTAMPTZ NOT NULL,
+    altitude_before DOUBLE PRECISION NOT NULL,
+    altitude_after DOUBLE PRECISION NOT NULL,
+    delta_km DOUBLE PRECISION NOT NULL,
+    decay_km_per_day DOUBLE PRECISION,
+    detected_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS idx_iss_orbit_events_norad_at ON iss_orbit_events (norad_id, at DESC);
 
 CREATE TABLE IF NOT EXISTS telemetry_legacy (
     id BIGSERIAL PRIMARY KEY,
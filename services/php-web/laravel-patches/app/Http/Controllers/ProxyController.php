@@ -22,6 +22,11 @@ class ProxyController extends Controller
         return $this->forward('/iss/trend', $request->query());
     }
 
+    public function passes(Request $request)
+    {
+        return $this->forward('/iss/passes', $request->query());
+    }
+
     private function forward(string $path, array $query = [])
     {
         try {
@@ -14,6 +14,7 @@ Route::get('/osdr', [OsdrController::class, 'index']);
 
 Route::get('/api/iss/last', [ProxyController::class, 'last']);
 Route::get('/api/iss/trend', [ProxyController::class, 'trend']);
+Route::get('/api/iss/passes', [ProxyController::class, 'passes']);
 
 Route::get('/api/jwst/feed', [DashboardController::class, 'jwstFeed']);
 Route::get('/api/astro/events', [AstroController::class, 'events']);
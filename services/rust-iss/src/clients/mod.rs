@@ -1,12 +1,18 @@
-use anyhow::Result;
-use reqwest::Client;
-use serde_json::Value;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use reqwest::{Client, Url};
+use serde_json::{json, Value};
 
-use crate::config::AppConfig;
+use crate::{
+    config::AppConfig,
+    domain::{OsdrFile, ISS_NORAD_ID},
+    orbit::Tle,
+};
 
 #[derive(Clone)]
 pub struct ExternalClients {
     pub iss: IssClient,
+    pub tle: TleClient,
     pub osdr: OsdrClient,
     pub nasa: NasaClient,
     pub spacex: SpacexClient,
@@ -20,51 +26,174 @@ impl ExternalClients {
             .build()?;
 
         Ok(Self {
-            iss: IssClient::new(client.clone(), &config.iss_url),
-            osdr: OsdrClient::new(client.clone(), &config.osdr_url),
+            iss: IssClient::new(client.clone(), iss_endpoints(config)?),
+            tle: TleClient::new(client.clone(), &config.tle_url),
+            osdr: OsdrClient::new(client.clone(), config),
             nasa: NasaClient::new(client.clone(), config.nasa_api_key.clone()),
             spacex: SpacexClient::new(client.clone()),
         })
     }
 }
 
+/// An upstream reporting satellite positions. Each adapter rewrites its payload into the
+/// wheretheiss.at shape that `IssPosition::from_payload` reads.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IssProvider {
+    WhereTheIss,
+    OpenNotify,
+}
+
+impl IssProvider {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "wheretheiss" | "wheretheiss.at" => Some(Self::WhereTheIss),
+            "open_notify" | "open-notify" | "opennotify" => Some(Self::OpenNotify),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::WhereTheIss => "wheretheiss",
+            Self::OpenNotify => "open_notify",
+        }
+    }
+
+    /// open-notify only ever reports the ISS.
+    fn supports(&self, norad_id: i32) -> bool {
+        match self {
+            Self::WhereTheIss => true,
+            Self::OpenNotify => norad_id == ISS_NORAD_ID,
+        }
+    }
+
+    pub fn adapt(&self, payload: Value) -> Result<Value> {
+        match self {
+            Self::WhereTheIss => {
+                if payload.get("latitude").and_then(Value::as_f64).is_none() {
+                    return Err(anyhow!("payload has no latitude"));
+                }
+                Ok(payload)
+            }
+            Self::OpenNotify => {
+                // {"message": "success", "timestamp": 1700000000,
+                //  "iss_position": {"latitude": "12.34", "longitude": "-56.78"}}
+                let position = payload.get("iss_position").context("payload has no iss_position")?;
+                let coordinate = |key: &str| {
+                    let value = position.get(key)?;
+                    value.as_f64().or_else(|| value.as_str()?.trim().parse().ok())
+                };
+                let latitude = coordinate("latitude").context("payload has no latitude")?;
+                let longitude = coordinate("longitude").context("payload has no longitude")?;
+                Ok(json!({
+                    "name": "iss",
+                    "id": ISS_NORAD_ID,
+                    "latitude": latitude,
+                    "longitude": longitude,
+                    "timestamp": payload.get("timestamp").and_then(Value::as_i64),
+                }))
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+struct IssEndpoint {
+    provider: IssProvider,
+    url_template: String,
+}
+
+/// A normalised payload and where it came from.
+#[derive(Debug, Clone)]
+pub struct IssFetch {
+    pub provider: IssProvider,
+    pub source_url: String,
+    pub payload: Value,
+}
+
+fn iss_endpoints(config: &AppConfig) -> Result<Vec<IssEndpoint>> {
+    config
+        .iss_providers
+        .iter()
+        .map(|name| {
+            let provider = IssProvider::parse(name).ok_or_else(|| anyhow!("unknown ISS provider `{name}`"))?;
+            let url_template = match provider {
+                IssProvider::WhereTheIss => config.satellite_url_template.clone(),
+                IssProvider::OpenNotify => config.open_notify_url.clone(),
+            };
+            Ok(IssEndpoint { provider, url_template })
+        })
+        .collect()
+}
+
+/// Fetches satellite positions from the configured providers, failing over in order.
 #[derive(Clone)]
 pub struct IssClient {
     client: Client,
-    url: String,
+    endpoints: Vec<IssEndpoint>,
 }
 
 impl IssClient {
-    fn new(client: Client, url: &str) -> Self {
-        Self {
-            client,
-            url: url.to_string(),
+    fn new(client: Client, endpoints: Vec<IssEndpoint>) -> Self {
+        Self { client, endpoints }
+    }
+
+    /// Returns the first provider that answers with a usable position; the error lists
+    /// every provider's failure when none does.
+    pub async fn fetch(&self, norad_id: i32) -> Result<IssFetch> {
+        let mut failures = Vec::new();
+        for endpoint in self.endpoints.iter().filter(|e| e.provider.supports(norad_id)) {
+            match self.fetch_from(endpoint, norad_id).await {
+                Ok(fetched) => return Ok(fetched),
+                Err(err) => {
+                    tracing::warn!(norad_id, provider = endpoint.provider.as_str(), error = %err, "provider failed");
+                    failures.push(format!("{}: {err}", endpoint.provider.as_str()));
+                }
+            }
+        }
+        if failures.is_empty() {
+            return Err(anyhow!("no configured ISS provider supports NORAD {norad_id}"));
         }
+        Err(anyhow!("all ISS providers failed ({})", failures.join("; ")))
     }
 
-    pub fn url(&self) -> &str {
-        &self.url
+    /// Fetches from the first provider other than `primary`, for cross-checking. `None`
+    /// when no other configured provider covers the satellite.
+    pub async fn fetch_secondary(&self, norad_id: i32, primary: IssProvider) -> Option<Result<IssFetch>> {
+        let endpoint = self
+            .endpoints
+            .iter()
+            .find(|e| e.provider != primary && e.provider.supports(norad_id))?;
+        Some(self.fetch_from(endpoint, norad_id).await)
     }
 
-    pub async fn fetch(&self) -> Result<Value> {
-        Ok(self
+    async fn fetch_from(&self, endpoint: &IssEndpoint, norad_id: i32) -> Result<IssFetch> {
+        let source_url = endpoint.url_template.replace("{norad_id}", &norad_id.to_string());
+        let payload: Value = self
             .client
-            .get(&self.url)
+            .get(&source_url)
             .send()
             .await?
             .error_for_status()?
             .json()
-            .await?)
+            .await?;
+        Ok(IssFetch {
+            provider: endpoint.provider,
+            payload: endpoint.provider.adapt(payload)?,
+            source_url,
+        })
     }
 }
 
+/// Fetches two-line element sets over HTTP, or from disk when the URL uses `file://`
+/// (handy for offline runs and tests).
 #[derive(Clone)]
-pub struct OsdrClient {
+pub struct TleClient {
     client: Client,
     url: String,
 }
 
-impl OsdrClient {
+impl TleClient {
     fn new(client: Client, url: &str) -> Self {
         Self {
             client,
@@ -72,27 +201,254 @@ impl OsdrClient {
         }
     }
 
-    pub async fn fetch(&self) -> Result<Vec<Value>> {
-        let json: Value = self
+    pub async fn fetch(&self) -> Result<Tle> {
+        let text = if let Some(path) = self.url.strip_prefix("file://") {
+            tokio::fs::read_to_string(path).await?
+        } else {
+            self.client
+                .get(&self.url)
+                .send()
+                .await?
+                .error_for_status()?
+                .text()
+                .await?
+        };
+        Tle::parse(&text)
+    }
+}
+
+#[derive(Clone)]
+pub struct OsdrClient {
+    client: Client,
+    url: String,
+    page_size: i64,
+    max_pages: usize,
+    since_param: Option<String>,
+    files_url: String,
+}
+
+/// Every item collected by following the OSDR pagination.
+#[derive(Debug, Clone, Default)]
+pub struct OsdrFetch {
+    pub items: Vec<Value>,
+    pub pages: usize,
+    pub truncated: bool,
+}
+
+#[derive(Debug, PartialEq)]
+enum NextPage {
+    Link(Url),
+    Offset(i64),
+}
+
+impl OsdrClient {
+    fn new(client: Client, config: &AppConfig) -> Self {
+        Self {
+            client,
+            url: config.osdr_url.clone(),
+            page_size: config.osdr_page_size,
+            max_pages: config.osdr_max_pages,
+            since_param: config.osdr_since_param.clone(),
+            files_url: config.osdr_files_url.clone(),
+        }
+    }
+
+    /// Lists the files of one dataset through the per-dataset files API. Returns the URL
+    /// that was queried alongside the parsed entries.
+    pub async fn files(&self, dataset_id: &str) -> Result<(String, Vec<OsdrFile>)> {
+        let url = files_url(&self.files_url, dataset_id)?;
+        let body: Value = self
             .client
-            .get(&self.url)
+            .get(url.clone())
             .send()
             .await?
             .error_for_status()?
             .json()
             .await?;
+        Ok((url.to_string(), parse_files(&body, &url)))
+    }
+
+    /// Whether the upstream can filter by modification time (`OSDR_SINCE_PARAM`).
+    pub fn supports_since(&self) -> bool {
+        self.since_param.is_some()
+    }
+
+    /// Identifies the upstream for per-source sync state.
+    pub fn source(&self) -> &str {
+        &self.url
+    }
 
-        if let Some(items) = json.as_array() {
-            return Ok(items.clone());
+    /// Follows `next` links, or `limit`/`offset` when the upstream reports a total, until
+    /// a short or empty page. A page repeating the previous one ends the crawl, since that
+    /// means the upstream ignores the paging parameters.
+    /// With `since` set and a configured since-parameter, only datasets modified after it
+    /// are requested.
+    pub async fn fetch(&self, since: Option<DateTime<Utc>>) -> Result<OsdrFetch> {
+        let mut base = Url::parse(&self.url).with_context(|| format!("invalid OSDR url {}", self.url))?;
+        if let (Some(param), Some(since)) = (&self.since_param, since) {
+            base.query_pairs_mut()
+                .append_pair(param, &since.to_rfc3339_opts(SecondsFormat::Secs, true));
         }
-        if let Some(items) = json.get("items").and_then(|v| v.as_array()) {
-            return Ok(items.clone());
+        let mut url = page_url(&base, self.page_size, 0);
+        let mut offset = 0i64;
+        let mut previous_first: Option<Value> = None;
+        let mut fetch = OsdrFetch::default();
+        loop {
+            let body: Value = self
+                .client
+                .get(url.clone())
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            fetch.pages += 1;
+            let items = page_items(body.clone());
+            if fetch.pages > 1 && !items.is_empty() && items.first() == previous_first.as_ref() {
+                tracing::warn!("OSDR page {} repeats the previous one, stopping", fetch.pages);
+                break;
+            }
+            previous_first = items.first().cloned();
+            let received = items.len();
+            fetch.items.extend(items);
+
+            let next = next_page(&body, &url, offset, received, self.page_size);
+            if next.is_some() && fetch.pages >= self.max_pages {
+                fetch.truncated = true;
+                break;
+            }
+            offset += received as i64;
+            url = match next {
+                Some(NextPage::Link(link)) => link,
+                Some(NextPage::Offset(next_offset)) => {
+                    offset = next_offset;
+                    page_url(&base, self.page_size, next_offset)
+                }
+                None => break,
+            };
         }
-        if let Some(items) = json.get("results").and_then(|v| v.as_array()) {
-            return Ok(items.clone());
+        Ok(fetch)
+    }
+}
+
+/// `base` with `limit`/`offset` replaced by the given page.
+fn page_url(base: &Url, page_size: i64, offset: i64) -> Url {
+    let mut url = base.clone();
+    let kept: Vec<(String, String)> = base
+        .query_pairs()
+        .filter(|(key, _)| key != "limit" && key != "offset")
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+    url.query_pairs_mut()
+        .clear()
+        .extend_pairs(kept)
+        .append_pair("limit", &page_size.to_string())
+        .append_pair("offset", &offset.to_string());
+    url
+}
+
+fn files_url(template: &str, dataset_id: &str) -> Result<Url> {
+    let mut raw = template.replace("{dataset_id}", dataset_id);
+    if raw.contains("{number}") {
+        let digits: String = dataset_id
+            .chars()
+            .rev()
+            .take_while(char::is_ascii_digit)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+            .collect();
+        if digits.is_empty() {
+            return Err(anyhow!("dataset id {dataset_id} has no accession number"));
         }
-        Ok(vec![json])
+        raw = raw.replace("{number}", &digits);
+    }
+    Url::parse(&raw).with_context(|| format!("invalid OSDR files url {raw}"))
+}
+
+/// File entries from a files API response: the OSDR `studies.<id>.study_files` layout,
+/// or a plain list (bare or under `files`/`items`/`results`/`data`). Relative download
+/// links are resolved against the request URL; entries without a name are skipped.
+fn parse_files(body: &Value, base: &Url) -> Vec<OsdrFile> {
+    let entries: Vec<&Value> = match body.get("studies").and_then(Value::as_object) {
+        Some(studies) => studies
+            .values()
+            .filter_map(|study| study.get("study_files")?.as_array())
+            .flatten()
+            .collect(),
+        None => body
+            .as_array()
+            .or_else(|| {
+                ["files", "study_files", "items", "results", "data"]
+                    .iter()
+                    .find_map(|key| body.get(key)?.as_array())
+            })
+            .map(|items| items.iter().collect())
+            .unwrap_or_default(),
+    };
+    let text = |entry: &Value, keys: &[&str]| {
+        keys.iter()
+            .find_map(|key| entry.get(key)?.as_str())
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+    entries
+        .into_iter()
+        .filter_map(|entry| {
+            let file_name = text(entry, &["file_name", "name", "filename"])?;
+            let size_bytes = ["file_size", "size", "bytes"].iter().find_map(|key| {
+                let value = entry.get(key)?;
+                value.as_i64().or_else(|| value.as_str()?.trim().parse().ok())
+            });
+            let download_url = text(entry, &["remote_url", "download_url", "url", "href"])
+                .and_then(|link| base.join(&link).ok())
+                .map(String::from);
+            let category = text(entry, &["category", "subcategory", "type"]);
+            Some(OsdrFile {
+                file_name,
+                size_bytes,
+                download_url,
+                category,
+            })
+        })
+        .collect()
+}
+
+/// Items of one list response: a bare array, or the array under `items`/`results`/`data`.
+pub fn page_items(body: Value) -> Vec<Value> {
+    match body {
+        Value::Array(items) => items,
+        Value::Object(mut map) => {
+            for key in ["items", "results", "data"] {
+                if let Some(Value::Array(items)) = map.remove(key) {
+                    return items;
+                }
+            }
+            vec![Value::Object(map)]
+        }
+        other => vec![other],
+    }
+}
+
+/// Works out the next page from a response: an explicit `next` link (DRF, JSON:API and
+/// HAL styles, possibly relative), else the next offset while a reported total remains.
+fn next_page(body: &Value, current: &Url, offset: i64, received: usize, page_size: i64) -> Option<NextPage> {
+    let link = ["/next", "/links/next", "/_links/next/href", "/paging/next"]
+        .iter()
+        .find_map(|pointer| body.pointer(pointer)?.as_str())
+        .filter(|link| !link.is_empty());
+    if let Some(link) = link {
+        return current.join(link).ok().map(NextPage::Link);
     }
+    if received == 0 {
+        return None;
+    }
+    let total = ["/count", "/total", "/totalCount", "/meta/total"]
+        .iter()
+        .find_map(|pointer| body.pointer(pointer)?.as_i64())?;
+    let next = offset + received as i64;
+    (next < total && received as i64 <= page_size).then_some(NextPage::Offset(next))
 }
 
 #[derive(Clone)]
@@ -177,3 +533,77 @@ impl SpacexClient {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn open_notify_payload_is_adapted_to_wheretheiss_shape() {
+        let raw = json!({
+            "message": "success",
+            "timestamp": 1_700_000_000,
+            "iss_position": { "latitude": "-12.5", "longitude": "101.25" }
+        });
+        let adapted = IssProvider::OpenNotify.adapt(raw).unwrap();
+        assert_eq!(adapted["latitude"], json!(-12.5));
+        assert_eq!(adapted["longitude"], json!(101.25));
+        assert_eq!(adapted["timestamp"], json!(1_700_000_000));
+        assert!(IssProvider::OpenNotify.adapt(json!({ "message": "failure" })).is_err());
+        assert!(IssProvider::WhereTheIss.adapt(json!({ "error": "rate limited" })).is_err());
+        assert!(!IssProvider::OpenNotify.supports(48_274));
+    }
+
+    #[test]
+    fn osdr_pages_follow_links_then_totals() {
+        let current = Url::parse("https://osdr.example/api/datasets/?format=json&limit=2&offset=0").unwrap();
+        let linked = json!({ "next": "?format=json&limit=2&offset=2", "results": [1, 2] });
+        assert_eq!(
+            next_page(&linked, &current, 0, 2, 2),
+            Some(NextPage::Link(
+                Url::parse("https://osdr.example/api/datasets/?format=json&limit=2&offset=2").unwrap()
+            ))
+        );
+        assert_eq!(next_page(&json!({ "next": null, "count": 2 }), &current, 0, 2, 2), None);
+        assert_eq!(next_page(&json!({ "total": 5, "items": [] }), &current, 2, 2, 2), Some(NextPage::Offset(4)));
+        assert_eq!(next_page(&json!({ "total": 5 }), &current, 4, 1, 2), None);
+        assert_eq!(next_page(&json!([1, 2]), &current, 0, 2, 2), None);
+
+        let paged = page_url(&current, 50, 100);
+        assert_eq!(paged.query(), Some("format=json&limit=50&offset=100"));
+    }
+
+    #[test]
+    fn osdr_files_are_read_from_the_studies_layout() {
+        let url = files_url("https://osdr.example/osdr/data/osd/files/{number}", "OSD-87").unwrap();
+        assert_eq!(url.as_str(), "https://osdr.example/osdr/data/osd/files/87");
+        assert!(files_url("https://osdr.example/files/{number}", "GLDS").is_err());
+
+        let body = json!({
+            "studies": {
+                "OSD-87": {
+                    "file_count": 2,
+                    "study_files": [
+                        {
+                            "file_name": "OSD-87_metadata.zip",
+                            "file_size": 52_311,
+                            "category": "Study Metadata Files",
+                            "remote_url": "/geode-py/ws/studies/OSD-87/download?file=OSD-87_metadata.zip"
+                        },
+                        { "file_size": "12" }
+                    ]
+                }
+            }
+        });
+        let files = parse_files(&body, &url);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].size_bytes, Some(52_311));
+        assert_eq!(
+            files[0].download_url.as_deref(),
+            Some("https://osdr.example/geode-py/ws/studies/OSD-87/download?file=OSD-87_metadata.zip")
+        );
+        let plain = parse_files(&json!([{ "name": "a.csv", "size": "10", "url": "https://cdn.example/a.csv" }]), &url);
+        assert_eq!(plain[0].size_bytes, Some(10));
+        assert_eq!(plain[0].download_url.as_deref(), Some("https://cdn.example/a.csv"));
+    }
+}
@@ -1,19 +1,80 @@
 use std::{env, net::SocketAddr, time::Duration};
 
 use anyhow::{anyhow, Context};
+use serde::Serialize;
 
 #[derive(Clone)]
 pub struct AppConfig {
     pub database: DatabaseConfig,
     pub server: ServerConfig,
     pub osdr_url: String,
-    pub iss_url: String,
+    pub satellite_url_template: String,
+    /// ISS position providers in failover order (`wheretheiss`, `open_notify`).
+    pub iss_providers: Vec<String>,
+    pub open_notify_url: String,
+    pub satellites: Vec<TrackedSatellite>,
+    pub tle_url: String,
     pub nasa_api_key: Option<String>,
     pub osdr_list_limit: i64,
+    /// Page size requested from OSDR (`limit`/`offset` query parameters).
+    pub osdr_page_size: i64,
+    /// Upper bound on pages followed per sync, as a guard against runaway pagination.
+    pub osdr_max_pages: usize,
+    /// Query parameter the OSDR upstream accepts for "modified since" filtering; when
+    /// unset every sync downloads the full list and unchanged payloads are skipped by hash.
+    pub osdr_since_param: Option<String>,
+    /// Per-dataset file listing endpoint; `{dataset_id}` is replaced by the accession
+    /// (`OSD-87`) and `{number}` by its numeric part (`87`).
+    pub osdr_files_url: String,
+    /// How long a stored file listing is served before the upstream is asked again.
+    pub osdr_files_ttl: Duration,
+    /// JSON field mapping for OSDR items; the bundled mapping is used when unset.
+    pub osdr_field_map_path: Option<String>,
+    /// Datasets absent from full syncs for longer than this are marked removed.
+    pub osdr_tombstone_grace: Duration,
+    /// With `osdr_since_param` set, a full crawl is still forced this often so removed
+    /// datasets can be detected.
+    pub osdr_full_sync_every: Duration,
     pub http_timeout: Duration,
     pub user_agent: String,
     pub scheduler: SchedulerConfig,
+    pub anomaly: AnomalyConfig,
+    pub orbit_analysis: OrbitAnalysisConfig,
     pub refresh_defaults: Vec<String>,
+    /// Raw `iss_fetch_log` rows older than this are pruned once rolled up; `None` keeps everything.
+    pub raw_retention_days: Option<i64>,
+    /// GeoJSON boundaries for reverse geocoding; the bundled coarse dataset is used when unset.
+    pub geo_boundaries_path: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct TrackedSatellite {
+    pub norad_id: i32,
+    pub name: String,
+}
+
+/// Plausibility limits for incoming satellite snapshots (defaults cover LEO objects).
+#[derive(Clone, Debug)]
+pub struct AnomalyConfig {
+    pub min_altitude_km: f64,
+    pub max_altitude_km: f64,
+    pub min_velocity_kmh: f64,
+    pub max_velocity_kmh: f64,
+    pub max_ground_speed_kmh: f64,
+    /// When set, every stored snapshot is checked against a second provider and flagged
+    /// if the two disagree by more than this many kilometres.
+    pub cross_check_km: Option<f64>,
+}
+
+/// Tuning for the altitude decay / reboost analysis over stored snapshots.
+#[derive(Clone, Debug)]
+pub struct OrbitAnalysisConfig {
+    /// History re-examined on every run; events already stored are not duplicated.
+    pub lookback_days: i64,
+    /// Smallest rise in orbit-averaged altitude reported as a reboost.
+    pub reboost_min_km: f64,
+    /// Buckets on each side of a candidate burn used to compare altitude levels.
+    pub reboost_span: usize,
 }
 
 #[derive(Clone)]
@@ -33,10 +94,13 @@ pub struct ServerConfig {
 pub struct SchedulerConfig {
     pub osdr: Duration,
     pub iss: Duration,
+    pub tle: Duration,
     pub apod: Duration,
     pub neo: Duration,
     pub donki: Duration,
     pub spacex: Duration,
+    pub rollup: Duration,
+    pub orbit_analysis: Duration,
 }
 
 impl AppConfig {
@@ -48,26 +112,81 @@ impl AppConfig {
             .unwrap_or_else(|_| "https://visualization.osdr.nasa.gov/biodata/api/v2/datasets/?format=json".to_string());
         let iss_url = env::var("WHERE_ISS_URL")
             .unwrap_or_else(|_| "https://api.wheretheiss.at/v1/satellites/25544".to_string());
+        let satellite_url_template = env::var("SATELLITE_URL_TEMPLATE")
+            .ok()
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(|| iss_url.replace("25544", "{norad_id}"));
+        let iss_providers = env::var("ISS_PROVIDERS")
+            .map(|raw| parse_sources(&raw))
+            .ok()
+            .filter(|list| !list.is_empty())
+            .unwrap_or_else(|| vec!["wheretheiss".to_string(), "open_notify".to_string()]);
+        let open_notify_url =
+            env::var("OPEN_NOTIFY_URL").unwrap_or_else(|_| "http://api.open-notify.org/iss-now.json".to_string());
+        let satellites = env::var("TRACKED_SATELLITES")
+            .map(|raw| parse_satellites(&raw))
+            .ok()
+            .filter(|list| !list.is_empty())
+            .unwrap_or_else(|| {
+                vec![TrackedSatellite {
+                    norad_id: 25544,
+                    name: "ISS".to_string(),
+                }]
+            });
+        check_satellite_url_template(&satellite_url_template, &satellites)?;
+        let tle_url = env::var("TLE_URL")
+            .unwrap_or_else(|_| "https://celestrak.org/NORAD/elements/gp.php?CATNR=25544&FORMAT=TLE".to_string());
         let nasa_api_key = env::var("NASA_API_KEY").ok().filter(|v| !v.is_empty());
         let osdr_list_limit = env_i64("OSDR_LIST_LIMIT", 20);
+        let osdr_page_size = env_i64("OSDR_PAGE_SIZE", 100).clamp(1, 1_000);
+        let osdr_max_pages = env_i64("OSDR_MAX_PAGES", 50).max(1) as usize;
+        let osdr_since_param = env::var("OSDR_SINCE_PARAM").ok().filter(|v| !v.is_empty());
+        let osdr_files_url = env::var("OSDR_FILES_URL")
+            .ok()
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(|| "https://osdr.nasa.gov/osdr/data/osd/files/{number}".to_string());
+        let osdr_files_ttl = env_duration("OSDR_FILES_TTL_SECONDS", 86_400);
+        let osdr_field_map_path = env::var("OSDR_FIELD_MAP_PATH").ok().filter(|v| !v.is_empty());
+        let osdr_tombstone_grace = env_duration("OSDR_TOMBSTONE_GRACE_SECONDS", 259_200);
+        let osdr_full_sync_every = env_duration("OSDR_FULL_SYNC_EVERY_SECONDS", 86_400);
         let http_timeout = env_duration("HTTP_TIMEOUT_SECONDS", 30);
         let user_agent = env::var("HTTP_USER_AGENT").unwrap_or_else(|_| "rust_iss/1.0".to_string());
         let scheduler = SchedulerConfig::load();
+        let anomaly = AnomalyConfig::load();
+        let orbit_analysis = OrbitAnalysisConfig::load();
         let refresh_defaults = env::var("SPACE_REFRESH_DEFAULTS")
             .map(|raw| parse_sources(&raw))
             .unwrap_or_else(|_| vec!["apod", "neo", "flr", "cme", "spacex"].into_iter().map(String::from).collect());
+        let raw_retention_days = Some(env_i64("ISS_RAW_RETENTION_DAYS", 0)).filter(|days| *days > 0);
+        let geo_boundaries_path = env::var("GEO_BOUNDARIES_PATH").ok().filter(|v| !v.is_empty());
 
         Ok(Self {
             database,
             server,
             osdr_url,
-            iss_url,
+            satellite_url_template,
+            iss_providers,
+            open_notify_url,
+            satellites,
+            tle_url,
             nasa_api_key,
             osdr_list_limit,
+            osdr_page_size,
+            osdr_max_pages,
+            osdr_since_param,
+            osdr_files_url,
+            osdr_files_ttl,
+            osdr_field_map_path,
+            osdr_tombstone_grace,
+            osdr_full_sync_every,
             http_timeout,
             user_agent,
             scheduler,
+            anomaly,
+            orbit_analysis,
             refresh_defaults,
+            raw_retention_days,
+            geo_boundaries_path,
         })
     }
 }
@@ -103,10 +222,36 @@ impl SchedulerConfig {
         Self {
             osdr: env_duration("FETCH_EVERY_SECONDS", 600),
             iss: env_duration("ISS_EVERY_SECONDS", 120),
+            tle: env_duration("TLE_EVERY_SECONDS", 21_600),
             apod: env_duration("APOD_EVERY_SECONDS", 43_200),
             neo: env_duration("NEO_EVERY_SECONDS", 7_200),
             donki: env_duration("DONKI_EVERY_SECONDS", 3_600),
             spacex: env_duration("SPACEX_EVERY_SECONDS", 3_600),
+            rollup: env_duration("ROLLUP_EVERY_SECONDS", 3_600),
+            orbit_analysis: env_duration("ORBIT_ANALYSIS_EVERY_SECONDS", 3_600),
+        }
+    }
+}
+
+impl AnomalyConfig {
+    fn load() -> Self {
+        Self {
+            min_altitude_km: env_f64("ANOMALY_MIN_ALTITUDE_KM", 150.0),
+            max_altitude_km: env_f64("ANOMALY_MAX_ALTITUDE_KM", 1_000.0),
+            min_velocity_kmh: env_f64("ANOMALY_MIN_VELOCITY_KMH", 20_000.0),
+            max_velocity_kmh: env_f64("ANOMALY_MAX_VELOCITY_KMH", 30_000.0),
+            max_ground_speed_kmh: env_f64("ANOMALY_MAX_GROUND_SPEED_KMH", 40_000.0),
+            cross_check_km: Some(env_f64("ISS_CROSS_CHECK_KM", 0.0)).filter(|km| *km > 0.0),
+        }
+    }
+}
+
+impl OrbitAnalysisConfig {
+    fn load() -> Self {
+        Self {
+            lookback_days: env_i64("ORBIT_ANALYSIS_LOOKBACK_DAYS", 14).max(1),
+            reboost_min_km: env_f64("ORBIT_REBOOST_MIN_KM", 0.5),
+            reboost_span: env_i64("ORBIT_REBOOST_SPAN_BUCKETS", 8).clamp(2, 64) as usize,
         }
     }
 }
@@ -131,6 +276,10 @@ fn env_i64(key: &str, default: i64) -> i64 {
     env::var(key).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
 }
 
+fn env_f64(key: &str, default: f64) -> f64 {
+    env::var(key).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+}
+
 fn parse_sources(raw: &str) -> Vec<String> {
     raw.split(',')
         .map(|s| s.trim().to_lowercase())
@@ -138,3 +287,54 @@ fn parse_sources(raw: &str) -> Vec<String> {
         .collect()
 }
 
+/// Parses `25544:ISS,48274:Tiangong,20580` into tracked satellites; the name is optional.
+fn parse_satellites(raw: &str) -> Vec<TrackedSatellite> {
+    raw.split(',')
+        .filter_map(|item| {
+            let mut parts = item.splitn(2, ':');
+            let norad_id = parts.next()?.trim().parse().ok()?;
+            let name = parts
+                .next()
+                .map(|n| n.trim().to_string())
+                .filter(|n| !n.is_empty())
+                .unwrap_or_else(|| format!("NORAD {norad_id}"));
+            Some(TrackedSatellite { norad_id, name })
+        })
+        .collect()
+}
+
+/// Without a `{norad_id}` placeholder every satellite would be fetched from the same URL
+/// and the ISS data stored under each id, so only the ISS may be tracked then.
+fn check_satellite_url_template(template: &str, satellites: &[TrackedSatellite]) -> anyhow::Result<()> {
+    if template.contains("{norad_id}") || satellites.iter().all(|sat| sat.norad_id == 25544) {
+        return Ok(());
+    }
+    Err(anyhow!(
+        "satellite URL template {template} has no {{norad_id}} placeholder; \
+         set SATELLITE_URL_TEMPLATE to track satellites other than the ISS"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_satellites_reads_ids_and_optional_names() {
+        let satellites = parse_satellites(" 25544:ISS, 48274 : Tiangong ,20580,bogus:Nope,,33591:");
+        let parsed: Vec<(i32, &str)> = satellites.iter().map(|sat| (sat.norad_id, sat.name.as_str())).collect();
+        assert_eq!(
+            parsed,
+            vec![(25544, "ISS"), (48274, "Tiangong"), (20580, "NORAD 20580"), (33591, "NORAD 33591")]
+        );
+        assert!(parse_satellites("").is_empty());
+    }
+
+    #[test]
+    fn satellite_url_template_needs_placeholder_beyond_the_iss() {
+        let satellites = parse_satellites("25544:ISS,48274:Tiangong");
+        assert!(check_satellite_url_template("https://example.test/v1/satellites/{norad_id}", &satellites).is_ok());
+        assert!(check_satellite_url_template("https://example.test/iss", &satellites).is_err());
+        assert!(check_satellite_url_template("https://example.test/iss", &satellites[..1]).is_ok());
+    }
+}
@@ -1,5 +1,7 @@
-use chrono::{DateTime, Utc};
-use serde::Serialize;
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, DurationRound, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 #[derive(Debug, Clone, Serialize)]
@@ -8,21 +10,109 @@ pub struct HealthDto {
     pub now: DateTime<Utc>,
 }
 
+pub const ISS_NORAD_ID: i32 = 25544;
+
 #[derive(Debug, Clone, Serialize)]
 pub struct IssEntry {
     pub id: i64,
+    pub norad_id: i32,
     pub fetched_at: DateTime<Utc>,
     pub source_url: String,
     pub payload: Value,
+    pub position: Option<IssPosition>,
+    pub anomalies: Vec<IssAnomaly>,
 }
 
-#[derive(Debug, Clone)]
-pub struct IssTrendPoints {
-    pub newest: IssEntry,
-    pub previous: IssEntry,
+/// Result of one poll: either a freshly stored row or the existing row it duplicated.
+#[derive(Debug, Clone, Serialize)]
+pub struct IssSnapshot {
+    pub duplicate: bool,
+    pub entry: IssEntry,
+    pub cross_check: Option<IssCrossCheck>,
 }
 
+/// Comparison of the stored snapshot with a second provider. The allowance grows with
+/// the gap between the two observation times, since the ISS moves ~7.7 km every second.
 #[derive(Debug, Clone, Serialize)]
+pub struct IssCrossCheck {
+    pub provider: String,
+    pub source_url: String,
+    pub distance_km: f64,
+    pub dt_sec: f64,
+    pub allowed_km: f64,
+    pub agrees: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssAnomaly {
+    MissingPosition,
+    DuplicateTimestamp,
+    FrozenPosition,
+    Teleport,
+    AltitudeOutOfBand,
+    VelocityOutOfRange,
+    ProviderDisagreement,
+}
+
+impl IssAnomaly {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::MissingPosition => "missing_position",
+            Self::DuplicateTimestamp => "duplicate_timestamp",
+            Self::FrozenPosition => "frozen_position",
+            Self::Teleport => "teleport",
+            Self::AltitudeOutOfBand => "altitude_out_of_band",
+            Self::VelocityOutOfRange => "velocity_out_of_range",
+            Self::ProviderDisagreement => "provider_disagreement",
+        }
+    }
+
+    pub fn parse(raw: &str) -> Option<Self> {
+        [
+            Self::MissingPosition,
+            Self::DuplicateTimestamp,
+            Self::FrozenPosition,
+            Self::Teleport,
+            Self::AltitudeOutOfBand,
+            Self::VelocityOutOfRange,
+            Self::ProviderDisagreement,
+        ]
+        .into_iter()
+        .find(|a| a.as_str() == raw)
+    }
+}
+
+/// Typed view of an upstream ISS snapshot, stored in dedicated columns of `iss_fetch_log`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IssPosition {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: Option<f64>,
+    pub velocity: Option<f64>,
+    pub visibility: Option<String>,
+    pub footprint: Option<f64>,
+    pub observed_at: Option<DateTime<Utc>>,
+}
+
+impl IssPosition {
+    pub fn from_payload(payload: &Value) -> Option<Self> {
+        Some(Self {
+            latitude: payload.get("latitude").and_then(Value::as_f64)?,
+            longitude: payload.get("longitude").and_then(Value::as_f64)?,
+            altitude: payload.get("altitude").and_then(Value::as_f64),
+            velocity: payload.get("velocity").and_then(Value::as_f64),
+            visibility: payload.get("visibility").and_then(Value::as_str).map(String::from),
+            footprint: payload.get("footprint").and_then(Value::as_f64),
+            observed_at: payload
+                .get("timestamp")
+                .and_then(Value::as_i64)
+                .and_then(|ts| Utc.timestamp_opt(ts, 0).single()),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct IssTrend {
     pub movement: bool,
     pub delta_km: f64,
@@ -34,6 +124,489 @@ pub struct IssTrend {
     pub from_lon: Option<f64>,
     pub to_lat: Option<f64>,
     pub to_lon: Option<f64>,
+    pub samples: usize,
+    pub window_sec: f64,
+    pub total_distance_km: f64,
+    pub mean_velocity_kmh: Option<f64>,
+    pub min_velocity_kmh: Option<f64>,
+    pub max_velocity_kmh: Option<f64>,
+    pub min_altitude_km: Option<f64>,
+    pub max_altitude_km: Option<f64>,
+    pub mean_gap_sec: Option<f64>,
+    pub max_gap_sec: Option<f64>,
+    pub segments: Vec<IssTrendSegment>,
+    pub points: Vec<IssTrackPoint>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IssTrendSegment {
+    pub from_time: DateTime<Utc>,
+    pub to_time: DateTime<Utc>,
+    pub delta_km: f64,
+    pub dt_sec: f64,
+    pub ground_speed_kmh: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IssTrackPoint {
+    pub at: DateTime<Utc>,
+    pub lat: f64,
+    pub lon: f64,
+    pub altitude: Option<f64>,
+    pub velocity: Option<f64>,
+    pub propagated: bool,
+}
+
+/// Position at an arbitrary instant, interpolated along the great circle between the two
+/// stored samples around it. `trusted` is false when those samples are too far apart.
+#[derive(Debug, Clone, Serialize)]
+pub struct IssInterpolatedPosition {
+    pub at: DateTime<Utc>,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: Option<f64>,
+    pub velocity: Option<f64>,
+    pub exact: bool,
+    pub trusted: bool,
+    pub gap_sec: f64,
+    pub fraction: f64,
+    pub before: IssTrackPoint,
+    pub after: IssTrackPoint,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IssTrack {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub raw_points: usize,
+    pub points: Vec<IssTrackPoint>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PredictedPosition {
+    pub at: DateTime<Utc>,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: f64,
+    pub velocity: f64,
+    pub tle_epoch: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Observer {
+    pub lat: f64,
+    pub lon: f64,
+    pub alt_m: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IssPass {
+    pub rise_time: DateTime<Utc>,
+    pub rise_azimuth: f64,
+    pub culmination_time: DateTime<Utc>,
+    pub culmination_azimuth: f64,
+    pub max_elevation: f64,
+    pub culmination_range_km: f64,
+    pub set_time: DateTime<Utc>,
+    pub set_azimuth: f64,
+    pub duration_sec: f64,
+    pub sunlit: bool,
+    pub observer_dark: bool,
+    pub visible: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IssPassPrediction {
+    pub observer: Observer,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub tle_epoch: DateTime<Utc>,
+    pub passes: Vec<IssPass>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RollupPeriod {
+    Hourly,
+    Daily,
+}
+
+impl RollupPeriod {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "hourly" | "hour" => Some(Self::Hourly),
+            "daily" | "day" => Some(Self::Daily),
+            _ => None,
+        }
+    }
+
+    pub fn table(&self) -> &'static str {
+        match self {
+            Self::Hourly => "iss_rollup_hourly",
+            Self::Daily => "iss_rollup_daily",
+        }
+    }
+
+    pub fn unit(&self) -> &'static str {
+        match self {
+            Self::Hourly => "hour",
+            Self::Daily => "day",
+        }
+    }
+
+    pub fn length(&self) -> chrono::Duration {
+        match self {
+            Self::Hourly => chrono::Duration::hours(1),
+            Self::Daily => chrono::Duration::days(1),
+        }
+    }
+
+    /// Start of the UTC bucket holding `at`, matching `date_trunc(unit, at, 'UTC')`.
+    pub fn bucket_start(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        at.duration_trunc(self.length()).unwrap_or(at)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IssRollup {
+    pub norad_id: i32,
+    pub bucket: DateTime<Utc>,
+    pub sample_count: i64,
+    pub avg_velocity: Option<f64>,
+    pub min_altitude: Option<f64>,
+    pub max_altitude: Option<f64>,
+    pub orbit_count: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RollupReport {
+    pub hourly_buckets: u64,
+    pub daily_buckets: u64,
+    pub pruned_rows: u64,
+}
+
+/// Orbit-averaged altitude over one bucket of clean samples.
+#[derive(Debug, Clone, Serialize)]
+pub struct AltitudeBucket {
+    pub at: DateTime<Utc>,
+    pub samples: i64,
+    pub mean_altitude: f64,
+    pub min_altitude: f64,
+    pub max_altitude: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AltitudeSeriesPoint {
+    #[serde(flatten)]
+    pub bucket: AltitudeBucket,
+    /// Trailing-window altitude trend in km/day (negative while decaying); the window
+    /// restarts after each detected burn so steps do not leak into the slope.
+    pub decay_km_per_day: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IssAltitudeSeries {
+    pub norad_id: i32,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub bucket_sec: i64,
+    pub window_hours: i64,
+    pub current_decay_km_per_day: Option<f64>,
+    pub events: Vec<OrbitEvent>,
+    pub points: Vec<AltitudeSeriesPoint>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrbitEventKind {
+    Reboost,
+    Lowering,
+}
+
+impl OrbitEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Reboost => "reboost",
+            Self::Lowering => "lowering",
+        }
+    }
+
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "reboost" => Some(Self::Reboost),
+            "lowering" => Some(Self::Lowering),
+            _ => None,
+        }
+    }
+}
+
+/// A step change in orbit-averaged altitude. `altitude_before` is the pre-burn trend
+/// extrapolated to the burn, so `delta_km` excludes the ongoing decay.
+#[derive(Debug, Clone, Serialize)]
+pub struct OrbitEvent {
+    pub id: i64,
+    pub norad_id: i32,
+    pub kind: OrbitEventKind,
+    pub at: DateTime<Utc>,
+    pub altitude_before: f64,
+    pub altitude_after: f64,
+    pub delta_km: f64,
+    pub decay_km_per_day: Option<f64>,
+    pub detected_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OrbitAnalysisReport {
+    pub buckets: usize,
+    pub detected: usize,
+    pub stored: usize,
+}
+
+/// Region a geofence covers. Polygons are a single ring of `[lon, lat]` vertices in
+/// GeoJSON order; the closing vertex is optional and rings may cross the antimeridian.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GeofenceShape {
+    Circle { lat: f64, lon: f64, radius_km: f64 },
+    Polygon { points: Vec<[f64; 2]> },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Geofence {
+    pub id: i64,
+    pub name: String,
+    pub shape: GeofenceShape,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GeofenceTransition {
+    Enter,
+    Exit,
+}
+
+impl GeofenceTransition {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Enter => "enter",
+            Self::Exit => "exit",
+        }
+    }
+
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "enter" => Some(Self::Enter),
+            "exit" => Some(Self::Exit),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GeofenceEvent {
+    pub id: i64,
+    pub geofence_id: i64,
+    pub norad_id: i32,
+    pub kind: GeofenceTransition,
+    pub entry_id: i64,
+    pub at: DateTime<Utc>,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GeoRegionKind {
+    Country,
+    Ocean,
+}
+
+/// Country or water body under a sub-satellite point.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GeoRegion {
+    pub name: String,
+    pub code: Option<String>,
+    pub kind: GeoRegionKind,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RegionDwell {
+    #[serde(flatten)]
+    pub region: GeoRegion,
+    pub seconds: f64,
+    pub share: f64,
+    pub samples: usize,
+}
+
+/// Time spent over each region in a window; intervals longer than `max_gap_sec` are not
+/// attributed and count towards `uncovered_sec` instead.
+#[derive(Debug, Clone, Serialize)]
+pub struct IssRegionTime {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub samples: usize,
+    pub max_gap_sec: f64,
+    pub covered_sec: f64,
+    pub uncovered_sec: f64,
+    pub items: Vec<RegionDwell>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SunlightState {
+    Sunlit,
+    Penumbra,
+    Umbra,
+}
+
+/// Illumination of the satellite from the conical Earth-shadow model. `sun_fraction` is
+/// the visible share of the solar disc; `ground_sun_elevation` is the Sun's elevation at
+/// the sub-satellite point, negative when the ground below is in night.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Sunlight {
+    pub state: SunlightState,
+    pub sun_fraction: f64,
+    pub ground_sun_elevation: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IssSunlightSample {
+    pub at: DateTime<Utc>,
+    pub lat: f64,
+    pub lon: f64,
+    pub altitude: f64,
+    #[serde(flatten)]
+    pub sunlight: Sunlight,
+}
+
+/// Illumination of every clean sample in a window. Samples without an altitude are
+/// skipped, since the shadow test depends on it.
+#[derive(Debug, Clone, Serialize)]
+pub struct IssSunlightReport {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub samples: usize,
+    pub sunlit: usize,
+    pub penumbra: usize,
+    pub umbra: usize,
+    pub items: Vec<IssSunlightSample>,
+}
+
+/// A clean stored sample together with its clean neighbours in the full history, so
+/// the segments leading into and out of it can be examined without scanning the window.
+#[derive(Debug, Clone)]
+pub struct IssTrackNeighbours {
+    pub prev: Option<IssTrackPoint>,
+    pub point: IssTrackPoint,
+    pub next: Option<IssTrackPoint>,
+}
+
+/// One interval during which the sub-satellite point was within the radius. Entry and
+/// exit are interpolated along the great circle between samples; `partial` marks an
+/// interval that starts or ends at a data gap rather than at an observed crossing.
+#[derive(Debug, Clone, Serialize)]
+pub struct IssOverflight {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub duration_sec: f64,
+    pub samples: usize,
+    pub closest_km: f64,
+    pub closest_at: DateTime<Utc>,
+    pub closest_lat: f64,
+    pub closest_lon: f64,
+    pub partial: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IssOverflights {
+    pub lat: f64,
+    pub lon: f64,
+    pub radius_km: f64,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub max_gap_sec: f64,
+    pub candidates: usize,
+    pub items: Vec<IssOverflight>,
+}
+
+/// Outcome of one OSDR sync. `truncated` is set when `OSDR_MAX_PAGES` stopped the crawl
+/// before the upstream ran out of pages; neither the watermark nor tombstones are updated
+/// in that case.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OsdrSyncReport {
+    pub run_id: i64,
+    pub source: String,
+    pub incremental: bool,
+    pub since: Option<DateTime<Utc>>,
+    pub watermark: Option<DateTime<Utc>>,
+    pub pages: usize,
+    pub items_seen: usize,
+    pub inserted: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    /// Items the field mapping rejected because a required field was missing.
+    pub skipped: usize,
+    /// Datasets tombstoned by this run; only full, untruncated syncs look for removals.
+    pub removed: usize,
+    pub restored: usize,
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpsertOutcome {
+    Inserted,
+    Updated,
+    Unchanged,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OsdrRevisionKind {
+    Created,
+    Updated,
+    /// Missing from full syncs for longer than the grace period.
+    Removed,
+    /// Seen again after having been marked removed.
+    Restored,
+}
+
+impl OsdrRevisionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::Updated => "updated",
+            Self::Removed => "removed",
+            Self::Restored => "restored",
+        }
+    }
+
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "created" => Some(Self::Created),
+            "updated" => Some(Self::Updated),
+            "removed" => Some(Self::Removed),
+            "restored" => Some(Self::Restored),
+            _ => None,
+        }
+    }
+}
+
+/// One recorded change to a dataset. `diff` holds JSON Patch style operations taking the
+/// previous `raw` payload to the new one; `previous_*` are unset for `created`.
+#[derive(Debug, Clone, Serialize)]
+pub struct OsdrRevision {
+    pub id: i64,
+    pub dataset_id: String,
+    pub sync_run_id: Option<i64>,
+    pub kind: OsdrRevisionKind,
+    pub recorded_at: DateTime<Utc>,
+    pub previous_updated_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub previous_title: Option<String>,
+    pub title: Option<String>,
+    pub previous_status: Option<String>,
+    pub status: Option<String>,
+    pub diff: Value,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -44,9 +617,162 @@ pub struct OsdrRecord {
     pub status: Option<String>,
     pub updated_at: Option<DateTime<Utc>>,
     pub inserted_at: DateTime<Utc>,
+    /// Last sync that returned this dataset, and when it was tombstoned for going missing.
+    pub last_seen_at: DateTime<Utc>,
+    pub removed_at: Option<DateTime<Utc>>,
     pub raw: Value,
 }
 
+/// Where a mapped field came from: the JSON pointer that matched and the value found
+/// there, or `defaulted` when no path matched and the rule's default was used.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OsdrFieldMatch {
+    pub path: Option<String>,
+    pub raw: Option<Value>,
+    pub defaulted: bool,
+}
+
+/// Result of applying a field mapping to one upstream item. `skipped` carries the reason
+/// when a required field could not be resolved; such items are not stored.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OsdrMappedItem {
+    pub dataset_id: Option<String>,
+    pub title: Option<String>,
+    pub status: Option<String>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub skipped: Option<String>,
+    pub fields: BTreeMap<String, OsdrFieldMatch>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OsdrMappingPreview {
+    pub source: String,
+    pub profile: String,
+    pub mapping: Value,
+    pub items: Vec<OsdrMappedItem>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OsdrFile {
+    pub file_name: String,
+    pub size_bytes: Option<i64>,
+    pub download_url: Option<String>,
+    pub category: Option<String>,
+}
+
+/// Files of one dataset. `cached` is set when the listing came from the database rather
+/// than a fresh upstream call, and `stale` when the upstream failed and an expired
+/// listing was served instead.
+#[derive(Debug, Clone, Serialize)]
+pub struct OsdrFileListing {
+    pub dataset_id: String,
+    pub source_url: Option<String>,
+    pub fetched_at: DateTime<Utc>,
+    pub cached: bool,
+    pub stale: bool,
+    pub files: Vec<OsdrFile>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OsdrSortKey {
+    InsertedAt,
+    UpdatedAt,
+    DatasetId,
+    Title,
+}
+
+impl OsdrSortKey {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "inserted_at" => Some(Self::InsertedAt),
+            "updated_at" => Some(Self::UpdatedAt),
+            "dataset_id" => Some(Self::DatasetId),
+            "title" => Some(Self::Title),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OsdrRemovedFilter {
+    #[default]
+    Exclude,
+    Include,
+    Only,
+}
+
+impl OsdrRemovedFilter {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Exclude => "exclude",
+            Self::Include => "include",
+            Self::Only => "only",
+        }
+    }
+
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "exclude" => Some(Self::Exclude),
+            "include" => Some(Self::Include),
+            "only" => Some(Self::Only),
+            _ => None,
+        }
+    }
+
+    /// Whether a dataset with this `removed_at` passes the filter.
+    pub fn matches(&self, removed_at: Option<DateTime<Utc>>) -> bool {
+        match self {
+            Self::Exclude => removed_at.is_none(),
+            Self::Include => true,
+            Self::Only => removed_at.is_some(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct OsdrListFilter {
+    pub removed: OsdrRemovedFilter,
+    pub status: Option<String>,
+    pub updated_from: Option<DateTime<Utc>>,
+    pub updated_to: Option<DateTime<Utc>>,
+    pub id_prefix: Option<String>,
+}
+
+/// Position after the last row of a page: the sort it belongs to, that row's sort key
+/// (`None` when the column was null) and its id as a tie-breaker.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OsdrCursor {
+    pub sort: OsdrSortKey,
+    pub descending: bool,
+    pub key: Option<String>,
+    pub id: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OsdrListPage {
+    pub items: Vec<OsdrRecord>,
+    pub next_cursor: Option<String>,
+}
+
+/// A full-text match. `snippet` is taken from the title and description as escaped
+/// HTML with matched terms wrapped in `<mark>`.
+#[derive(Debug, Clone, Serialize)]
+pub struct OsdrSearchHit {
+    pub dataset: OsdrRecord,
+    pub rank: f32,
+    pub snippet: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OsdrSearchPage {
+    pub q: String,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+    pub items: Vec<OsdrSearchHit>,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct SpaceCacheItem {
     pub source: String,
@@ -41,10 +41,6 @@ impl ApiError {
     pub fn from_error(code: &'static str, err: impl std::fmt::Display) -> Self {
         Self::new(code, err.to_string())
     }
-
-    pub fn tracing_code(&self) -> &str {
-        &self.body.trace_id
-    }
 }
 
 impl std::fmt::Display for ApiError {
@@ -0,0 +1,185 @@
+use std::fmt::Write;
+
+use chrono::SecondsFormat;
+use serde_json::{json, Value};
+
+use crate::{
+    domain::{IssTrack, IssTrackPoint},
+    solar,
+};
+
+/// Splits a time-ordered track into segments that never cross the antimeridian.
+/// Each crossing closes the current segment at ±180° and opens the next one on the
+/// opposite side, with time, latitude, altitude and velocity interpolated at the seam.
+pub fn split_antimeridian(points: &[IssTrackPoint]) -> Vec<Vec<IssTrackPoint>> {
+    let mut segments = Vec::new();
+    let mut current: Vec<IssTrackPoint> = Vec::new();
+    for point in points {
+        if let Some(prev) = current.last() {
+            let dlon = point.lon - prev.lon;
+            if dlon.abs() > 180.0 {
+                let edge = if dlon < 0.0 { 180.0 } else { -180.0 };
+                let unwrapped = point.lon + if dlon < 0.0 { 360.0 } else { -360.0 };
+                let t = (edge - prev.lon) / (unwrapped - prev.lon);
+                let seam = interpolate(prev, point, t);
+                current.push(IssTrackPoint { lon: edge, ..seam.clone() });
+                segments.push(std::mem::take(&mut current));
+                current.push(IssTrackPoint { lon: -edge, ..seam });
+            }
+        }
+        current.push(point.clone());
+    }
+    if !current.is_empty() {
+        segments.push(current);
+    }
+    segments
+}
+
+pub fn track_geojson(track: &IssTrack) -> Value {
+    let features: Vec<Value> = split_antimeridian(&track.points)
+        .iter()
+        .enumerate()
+        .filter(|(_, seg)| seg.len() >= 2)
+        .map(|(idx, seg)| {
+            let coordinates: Vec<Value> = seg
+                .iter()
+                .map(|p| match p.altitude {
+                    Some(alt) => json!([p.lon, p.lat, alt * 1_000.0]),
+                    None => json!([p.lon, p.lat]),
+                })
+                .collect();
+            json!({
+                "type": "Feature",
+                "geometry": { "type": "LineString", "coordinates": coordinates },
+                "properties": {
+                    "segment": idx,
+                    "from": seg.first().map(|p| p.at),
+                    "to": seg.last().map(|p| p.at),
+                    "times": seg.iter().map(|p| p.at).collect::<Vec<_>>(),
+                    "altitudes_km": seg.iter().map(|p| p.altitude).collect::<Vec<_>>(),
+                    "velocities_kmh": seg.iter().map(|p| p.velocity).collect::<Vec<_>>(),
+                    "propagated": seg.iter().map(|p| p.propagated).collect::<Vec<_>>(),
+                    "sunlight": seg
+                        .iter()
+                        .map(|p| p.altitude.map(|alt| solar::sunlight(p.lat, p.lon, alt, p.at).state))
+                        .collect::<Vec<_>>(),
+                }
+            })
+        })
+        .collect();
+
+    json!({
+        "type": "FeatureCollection",
+        "properties": { "from": track.from, "to": track.to, "raw_points": track.raw_points },
+        "features": features,
+    })
+}
+
+pub fn track_kml(track: &IssTrack) -> String {
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<kml xmlns=\"http://www.opengis.net/kml/2.2\">\n<Document>\n");
+    let _ = writeln!(out, "<name>ISS ground track {} – {}</name>", kml_time(&track.from), kml_time(&track.to));
+
+    out.push_str("<Folder>\n<name>Track</name>\n");
+    for (idx, seg) in split_antimeridian(&track.points).iter().enumerate() {
+        let (Some(first), Some(last)) = (seg.first(), seg.last()) else {
+            continue;
+        };
+        if seg.len() < 2 {
+            continue;
+        }
+        let _ = writeln!(out, "<Placemark>\n<name>Segment {idx}</name>");
+        let _ = writeln!(
+            out,
+            "<TimeSpan><begin>{}</begin><end>{}</end></TimeSpan>",
+            kml_time(&first.at),
+            kml_time(&last.at)
+        );
+        out.push_str("<LineString>\n<altitudeMode>absolute</altitudeMode>\n<coordinates>\n");
+        for p in seg {
+            let _ = writeln!(out, "{:.6},{:.6},{:.0}", p.lon, p.lat, p.altitude.unwrap_or(0.0) * 1_000.0);
+        }
+        out.push_str("</coordinates>\n</LineString>\n</Placemark>\n");
+    }
+    out.push_str("</Folder>\n");
+
+    out.push_str("<Folder>\n<name>Samples</name>\n");
+    for p in &track.points {
+        out.push_str("<Placemark>\n");
+        let _ = writeln!(out, "<TimeStamp><when>{}</when></TimeStamp>", kml_time(&p.at));
+        out.push_str("<ExtendedData>\n");
+        if let Some(alt) = p.altitude {
+            let _ = writeln!(out, "<Data name=\"altitude_km\"><value>{alt:.3}</value></Data>");
+        }
+        if let Some(vel) = p.velocity {
+            let _ = writeln!(out, "<Data name=\"velocity_kmh\"><value>{vel:.1}</value></Data>");
+        }
+        let _ = writeln!(out, "<Data name=\"propagated\"><value>{}</value></Data>", p.propagated);
+        out.push_str("</ExtendedData>\n");
+        let _ = writeln!(
+            out,
+            "<Point><altitudeMode>absolute</altitudeMode><coordinates>{:.6},{:.6},{:.0}</coordinates></Point>",
+            p.lon,
+            p.lat,
+            p.altitude.unwrap_or(0.0) * 1_000.0
+        );
+        out.push_str("</Placemark>\n");
+    }
+    out.push_str("</Folder>\n</Document>\n</kml>\n");
+    out
+}
+
+fn interpolate(a: &IssTrackPoint, b: &IssTrackPoint, t: f64) -> IssTrackPoint {
+    let lerp = |x: f64, y: f64| x + (y - x) * t;
+    let dt_ms = (b.at - a.at).num_milliseconds() as f64;
+    IssTrackPoint {
+        at: a.at + chrono::Duration::milliseconds((dt_ms * t).round() as i64),
+        lat: lerp(a.lat, b.lat),
+        lon: lerp(a.lon, b.lon),
+        altitude: a.altitude.zip(b.altitude).map(|(x, y)| lerp(x, y)),
+        velocity: a.velocity.zip(b.velocity).map(|(x, y)| lerp(x, y)),
+        propagated: a.propagated || b.propagated,
+    }
+}
+
+fn kml_time(at: &chrono::DateTime<chrono::Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn point(sec: i64, lat: f64, lon: f64) -> IssTrackPoint {
+        IssTrackPoint {
+            at: Utc.timestamp_opt(1_700_000_000 + sec, 0).unwrap(),
+            lat,
+            lon,
+            altitude: Some(420.0),
+            velocity: Some(27_600.0),
+            propagated: false,
+        }
+    }
+
+    #[test]
+    fn split_antimeridian_closes_segments_at_the_seam() {
+        let points = vec![point(0, 10.0, 170.0), point(60, 12.0, 178.0), point(120, 14.0, -174.0), point(180, 16.0, -166.0)];
+        let segments = split_antimeridian(&points);
+        assert_eq!(segments.len(), 2);
+        let seam_end = segments[0].last().unwrap();
+        let seam_start = segments[1].first().unwrap();
+        assert_eq!(seam_end.lon, 180.0);
+        assert_eq!(seam_start.lon, -180.0);
+        assert!((seam_end.lat - 12.5).abs() < 1e-9);
+        assert_eq!(seam_end.at, seam_start.at);
+        assert_eq!(segments[0].len() + segments[1].len(), points.len() + 2);
+    }
+
+    #[test]
+    fn split_antimeridian_keeps_continuous_track_whole() {
+        let points = vec![point(0, 0.0, -10.0), point(60, 1.0, 0.0), point(120, 2.0, 10.0)];
+        assert_eq!(split_antimeridian(&points).len(), 1);
+    }
+}
@@ -0,0 +1,276 @@
+use anyhow::{anyhow, Context, Result};
+use serde_json::Value;
+
+use crate::{
+    domain::{GeoRegion, GeoRegionKind},
+    orbit,
+};
+
+/// Coarse country outlines covering the land under the ISS ground track (roughly ±55°),
+/// simplified to a few dozen vertices per country. Override with `GEO_BOUNDARIES_PATH`
+/// (any GeoJSON FeatureCollection of Polygon/MultiPolygon features, e.g. Natural Earth).
+const BUNDLED_BOUNDARIES: &str = include_str!("boundaries.geojson");
+
+/// Offline point-in-polygon lookup of the country under a sub-satellite point, with a
+/// rule-based ocean/sea name when no boundary matches.
+pub struct ReverseGeocoder {
+    boundaries: Vec<Boundary>,
+}
+
+struct Boundary {
+    name: String,
+    code: Option<String>,
+    bbox: BBox,
+    /// Polygons as rings in `[lon, lat]`; the first ring is the shell, the rest are holes.
+    polygons: Vec<Vec<Vec<[f64; 2]>>>,
+}
+
+#[derive(Clone, Copy)]
+struct BBox {
+    min_lon: f64,
+    min_lat: f64,
+    max_lon: f64,
+    max_lat: f64,
+}
+
+impl ReverseGeocoder {
+    pub fn load(path: Option<&str>) -> Result<Self> {
+        let raw = match path {
+            Some(path) => {
+                std::fs::read_to_string(path).with_context(|| format!("failed to read boundaries from {path}"))?
+            }
+            None => BUNDLED_BOUNDARIES.to_string(),
+        };
+        let geojson: Value = serde_json::from_str(&raw).context("boundaries are not valid JSON")?;
+        Self::from_geojson(&geojson)
+    }
+
+    pub fn from_geojson(geojson: &Value) -> Result<Self> {
+        let features = geojson
+            .get("features")
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| anyhow!("boundaries must be a GeoJSON FeatureCollection"))?;
+        let boundaries: Vec<Boundary> = features.iter().filter_map(Boundary::from_feature).collect();
+        if boundaries.is_empty() {
+            return Err(anyhow!("boundaries contain no usable polygons"));
+        }
+        Ok(Self { boundaries })
+    }
+
+    pub fn boundary_count(&self) -> usize {
+        self.boundaries.len()
+    }
+
+    pub fn lookup(&self, lat: f64, lon: f64) -> GeoRegion {
+        let lon = orbit::normalize_lon(lon);
+        self.boundaries
+            .iter()
+            .find(|b| b.bbox.contains(lat, lon) && b.contains(lat, lon))
+            .map(|b| GeoRegion {
+                name: b.name.clone(),
+                code: b.code.clone(),
+                kind: GeoRegionKind::Country,
+            })
+            .unwrap_or_else(|| GeoRegion {
+                name: water_body(lat, lon).to_string(),
+                code: None,
+                kind: GeoRegionKind::Ocean,
+            })
+    }
+}
+
+impl Boundary {
+    fn from_feature(feature: &Value) -> Option<Self> {
+        let props = feature.get("properties")?;
+        let name = ["name", "NAME", "ADMIN", "admin"]
+            .iter()
+            .find_map(|key| props.get(*key).and_then(|v| v.as_str()))?
+            .to_string();
+        let code = ["iso_a2", "ISO_A2", "iso"]
+            .iter()
+            .find_map(|key| props.get(*key).and_then(|v| v.as_str()))
+            .filter(|code| *code != "-99")
+            .map(str::to_string);
+
+        let geometry = feature.get("geometry")?;
+        let coordinates = geometry.get("coordinates")?;
+        let polygons = match geometry.get("type")?.as_str()? {
+            "Polygon" => vec![parse_polygon(coordinates)?],
+            "MultiPolygon" => coordinates
+                .as_array()?
+                .iter()
+                .map(parse_polygon)
+                .collect::<Option<Vec<_>>>()?,
+            _ => return None,
+        };
+
+        let bbox = polygons.iter().flat_map(|p| p.first()).fold(None, |acc: Option<BBox>, ring| {
+            let ring_box = BBox::of_ring(ring);
+            Some(acc.map_or(ring_box, |b| b.union(ring_box)))
+        })?;
+        Some(Self {
+            name,
+            code,
+            bbox,
+            polygons,
+        })
+    }
+
+    fn contains(&self, lat: f64, lon: f64) -> bool {
+        self.polygons.iter().any(|rings| match rings.split_first() {
+            Some((shell, holes)) => {
+                ring_contains(shell, lat, lon) && !holes.iter().any(|hole| ring_contains(hole, lat, lon))
+            }
+            None => false,
+        })
+    }
+}
+
+impl BBox {
+    /// Box over the ring after antimeridian unwrapping, so it may extend past ±180°.
+    fn of_ring(ring: &[[f64; 2]]) -> Self {
+        let mut bbox = Self {
+            min_lon: f64::INFINITY,
+            min_lat: f64::INFINITY,
+            max_lon: f64::NEG_INFINITY,
+            max_lat: f64::NEG_INFINITY,
+        };
+        for (x, y) in unwrap_ring(ring) {
+            bbox.min_lon = bbox.min_lon.min(x);
+            bbox.max_lon = bbox.max_lon.max(x);
+            bbox.min_lat = bbox.min_lat.min(y);
+            bbox.max_lat = bbox.max_lat.max(y);
+        }
+        bbox
+    }
+
+    fn union(self, other: Self) -> Self {
+        Self {
+            min_lon: self.min_lon.min(other.min_lon),
+            min_lat: self.min_lat.min(other.min_lat),
+            max_lon: self.max_lon.max(other.max_lon),
+            max_lat: self.max_lat.max(other.max_lat),
+        }
+    }
+
+    fn contains(&self, lat: f64, lon: f64) -> bool {
+        lat >= self.min_lat
+            && lat <= self.max_lat
+            && [lon, lon + 360.0, lon - 360.0]
+                .into_iter()
+                .any(|x| x >= self.min_lon && x <= self.max_lon)
+    }
+}
+
+fn parse_polygon(value: &Value) -> Option<Vec<Vec<[f64; 2]>>> {
+    value
+        .as_array()?
+        .iter()
+        .map(|ring| {
+            ring.as_array()?
+                .iter()
+                .map(|pt| Some([pt.get(0)?.as_f64()?, pt.get(1)?.as_f64()?]))
+                .collect::<Option<Vec<_>>>()
+                .filter(|ring| ring.len() >= 3)
+        })
+        .collect()
+}
+
+fn unwrap_ring(ring: &[[f64; 2]]) -> Vec<(f64, f64)> {
+    let mut out: Vec<(f64, f64)> = Vec::with_capacity(ring.len());
+    for &[lon, lat] in ring {
+        let x = match out.last() {
+            Some(&(prev, _)) => prev + orbit::normalize_lon(lon - prev),
+            None => lon,
+        };
+        out.push((x, lat));
+    }
+    out
+}
+
+/// Even-odd ray cast on the lon/lat plane. Vertices are unwrapped so that each edge spans
+/// less than 180° of longitude, which keeps rings crossing the antimeridian contiguous;
+/// the point is then tested at its own longitude and shifted by ±360°.
+pub fn ring_contains(ring: &[[f64; 2]], lat: f64, lon: f64) -> bool {
+    let ring = unwrap_ring(ring);
+    if ring.len() < 3 {
+        return false;
+    }
+    [lon, lon + 360.0, lon - 360.0].into_iter().any(|x| {
+        let mut inside = false;
+        let mut j = ring.len() - 1;
+        for i in 0..ring.len() {
+            let (xi, yi) = ring[i];
+            let (xj, yj) = ring[j];
+            if (yi > lat) != (yj > lat) && x < (xj - xi) * (lat - yi) / (yj - yi) + xi {
+                inside = !inside;
+            }
+            j = i;
+        }
+        inside
+    })
+}
+
+/// Names the water body for a point that is not over land. Enclosed seas are matched by
+/// bounding box first, then oceans are split along the usual meridians and coastlines.
+fn water_body(lat: f64, lon: f64) -> &'static str {
+    if (46.5..=54.5).contains(&lon) && (36.5..=47.2).contains(&lat) {
+        return "Caspian Sea";
+    }
+    if (27.4..=41.8).contains(&lon) && (40.9..=46.8).contains(&lat) {
+        return "Black Sea";
+    }
+    if (-5.6..=36.2).contains(&lon) && (30.0..=45.8).contains(&lat) {
+        return "Mediterranean Sea";
+    }
+    if lat >= 66.0 {
+        return "Arctic Ocean";
+    }
+    if lat <= -60.0 {
+        return "Southern Ocean";
+    }
+    if (20.0..=147.0).contains(&lon) && lat <= 30.0 && !(lon >= 100.0 && lat >= -8.0) {
+        return "Indian Ocean";
+    }
+    // Western edge of the Atlantic follows the Americas' Pacific coast band by band.
+    let atlantic_west = match lat {
+        l if l < -55.0 => -67.0,
+        l if l < 9.0 => -70.0,
+        l if l < 13.0 => -84.0,
+        l if l < 18.0 => -88.0,
+        l if l < 31.0 => -98.0,
+        _ => -100.0,
+    };
+    let atlantic_east = if lat > 30.0 { 45.0 } else { 20.0 };
+    if lon >= atlantic_west && lon < atlantic_east {
+        "Atlantic Ocean"
+    } else {
+        "Pacific Ocean"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_boundaries_resolve_countries_and_oceans() {
+        let geocoder = ReverseGeocoder::load(None).unwrap();
+        let cases = [
+            (48.85, 2.35, "France"),
+            (55.75, 37.6, "Russia"),
+            (66.0, -172.0, "Russia"),
+            (-33.87, 151.1, "Australia"),
+            (39.9, 116.4, "China"),
+            (41.88, -87.7, "United States"),
+            (0.0, -150.0, "Pacific Ocean"),
+            (30.0, -40.0, "Atlantic Ocean"),
+            (-20.0, 80.0, "Indian Ocean"),
+            (35.0, 18.0, "Mediterranean Sea"),
+        ];
+        for (lat, lon, expected) in cases {
+            assert_eq!(geocoder.lookup(lat, lon).name, expected, "lookup({lat}, {lon})");
+        }
+        assert_eq!(geocoder.lookup(48.85, 2.35).code.as_deref(), Some("FR"));
+    }
+}
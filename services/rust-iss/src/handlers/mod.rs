@@ -1,12 +1,22 @@
-use axum::extract::{Path, Query, State};
-use chrono::Utc;
+use axum::{
+    extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Duration, Utc};
 use serde::Deserialize;
 use serde_json::json;
 
 use crate::{
-    domain::{HealthDto},
+    domain::{
+        GeofenceShape, HealthDto, IssTrack, Observer, OsdrListFilter, OsdrRemovedFilter, OsdrSortKey, RollupPeriod,
+        ISS_NORAD_ID,
+    },
     error::{respond, ApiError, ApiResult},
-    services::AppState,
+    export, osdr,
+    services::{AppState, ORBIT_BUCKET_SEC},
+    solar,
 };
 
 #[derive(Deserialize)]
@@ -14,9 +24,156 @@ pub struct RefreshQuery {
     src: Option<String>,
 }
 
+#[derive(Deserialize)]
+pub struct IssTrendQuery {
+    limit: Option<i64>,
+    include_anomalies: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct IssAnomaliesQuery {
+    limit: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct IssTrackQuery {
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    max_points: Option<usize>,
+    fill_sec: Option<i64>,
+    include_anomalies: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct IssPositionQuery {
+    at: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize)]
+pub struct IssAtQuery {
+    ts: Option<DateTime<Utc>>,
+    max_gap_sec: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct IssPassesQuery {
+    lat: Option<f64>,
+    lon: Option<f64>,
+    alt: Option<f64>,
+    days: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct IssRollupQuery {
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize)]
+pub struct IssRegionsQuery {
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    max_gap_sec: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct IssOverflightsQuery {
+    lat: Option<f64>,
+    lon: Option<f64>,
+    radius_km: Option<f64>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    max_gap_sec: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct IssSunlightQuery {
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize)]
+pub struct IssTerminatorQuery {
+    ts: Option<DateTime<Utc>>,
+    step_deg: Option<f64>,
+}
+
+#[derive(Deserialize)]
+pub struct OrbitEventsQuery {
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    limit: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct AltitudeSeriesQuery {
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    bucket_minutes: Option<i64>,
+    window_hours: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct GeofenceCreateRequest {
+    name: String,
+    shape: GeofenceShape,
+}
+
+#[derive(Deserialize)]
+pub struct GeofenceEventsQuery {
+    norad_id: Option<i32>,
+    since: Option<DateTime<Utc>>,
+    limit: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct OsdrSyncQuery {
+    full: Option<bool>,
+}
+
 #[derive(Deserialize)]
 pub struct OsdrListQuery {
     limit: Option<i64>,
+    removed: Option<String>,
+    status: Option<String>,
+    updated_from: Option<DateTime<Utc>>,
+    updated_to: Option<DateTime<Utc>>,
+    id_prefix: Option<String>,
+    sort: Option<String>,
+    order: Option<String>,
+    cursor: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct OsdrSearchQuery {
+    q: Option<String>,
+    removed: Option<String>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct OsdrMappingDryRunRequest {
+    source: Option<String>,
+    mapping: Option<serde_json::Value>,
+    payload: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+pub struct OsdrDatasetQuery {
+    removed: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct OsdrFilesQuery {
+    removed: Option<String>,
+    refresh: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct OsdrHistoryQuery {
+    since: Option<DateTime<Utc>>,
+    limit: Option<i64>,
 }
 
 pub async fn health() -> ApiResult<HealthDto> {
@@ -26,17 +183,330 @@ pub async fn health() -> ApiResult<HealthDto> {
     })
 }
 
+pub async fn satellites(State(state): State<AppState>) -> ApiResult<serde_json::Value> {
+    respond(json!({ "items": state.services.iss.satellites() }))
+}
+
 pub async fn last_iss(State(state): State<AppState>) -> ApiResult<serde_json::Value> {
+    last_for(&state, ISS_NORAD_ID).await
+}
+
+pub async fn satellite_last(
+    Path(norad_id): Path<i32>,
+    State(state): State<AppState>,
+) -> ApiResult<serde_json::Value> {
+    ensure_tracked(&state, norad_id)?;
+    last_for(&state, norad_id).await
+}
+
+pub async fn trigger_iss(State(state): State<AppState>) -> ApiResult<serde_json::Value> {
+    let svc = state.services.iss.clone();
+    let snapshot = svc
+        .trigger_fetch(ISS_NORAD_ID)
+        .await
+        .map_err(|err| ApiError::from_error("ISS_FETCH_FAILED", err))?;
+    let entry = snapshot.entry;
+    respond(json!({
+        "status": if snapshot.duplicate { "duplicate" } else { "new" },
+        "duplicate": snapshot.duplicate,
+        "id": entry.id,
+        "norad_id": entry.norad_id,
+        "fetched_at": entry.fetched_at,
+        "source_url": entry.source_url,
+        "position": entry.position,
+        "anomalies": entry.anomalies,
+        "cross_check": snapshot.cross_check,
+        "payload": entry.payload
+    }))
+}
+
+pub async fn iss_trend(
+    State(state): State<AppState>,
+    Query(query): Query<IssTrendQuery>,
+) -> ApiResult<crate::domain::IssTrend> {
+    trend_for(&state, ISS_NORAD_ID, query).await
+}
+
+pub async fn satellite_trend(
+    Path(norad_id): Path<i32>,
+    State(state): State<AppState>,
+    Query(query): Query<IssTrendQuery>,
+) -> ApiResult<crate::domain::IssTrend> {
+    ensure_tracked(&state, norad_id)?;
+    trend_for(&state, norad_id, query).await
+}
+
+pub async fn iss_track(
+    State(state): State<AppState>,
+    Query(query): Query<IssTrackQuery>,
+) -> ApiResult<IssTrack> {
+    track_for(&state, ISS_NORAD_ID, query).await
+}
+
+pub async fn iss_track_geojson(
+    State(state): State<AppState>,
+    Query(query): Query<IssTrackQuery>,
+) -> Result<Response, ApiError> {
+    let track = load_track(&state, ISS_NORAD_ID, query).await?;
+    Ok((
+        [(header::CONTENT_TYPE, "application/geo+json")],
+        Json(export::track_geojson(&track)),
+    )
+        .into_response())
+}
+
+pub async fn iss_terminator(Query(query): Query<IssTerminatorQuery>) -> Result<Response, ApiError> {
+    let at = query.ts.unwrap_or_else(Utc::now);
+    let step = query.step_deg.unwrap_or(1.0).clamp(0.25, 10.0);
+    Ok((
+        [(header::CONTENT_TYPE, "application/geo+json")],
+        Json(solar::terminator_geojson(at, step)),
+    )
+        .into_response())
+}
+
+pub async fn iss_track_kml(
+    State(state): State<AppState>,
+    Query(query): Query<IssTrackQuery>,
+) -> Result<Response, ApiError> {
+    let track = load_track(&state, ISS_NORAD_ID, query).await?;
+    Ok((
+        [(header::CONTENT_TYPE, "application/vnd.google-earth.kml+xml")],
+        export::track_kml(&track),
+    )
+        .into_response())
+}
+
+pub async fn satellite_track(
+    Path(norad_id): Path<i32>,
+    State(state): State<AppState>,
+    Query(query): Query<IssTrackQuery>,
+) -> ApiResult<IssTrack> {
+    ensure_tracked(&state, norad_id)?;
+    track_for(&state, norad_id, query).await
+}
+
+pub async fn iss_anomalies(
+    State(state): State<AppState>,
+    Query(query): Query<IssAnomaliesQuery>,
+) -> ApiResult<serde_json::Value> {
+    anomalies_for(&state, ISS_NORAD_ID, query).await
+}
+
+pub async fn satellite_anomalies(
+    Path(norad_id): Path<i32>,
+    State(state): State<AppState>,
+    Query(query): Query<IssAnomaliesQuery>,
+) -> ApiResult<serde_json::Value> {
+    ensure_tracked(&state, norad_id)?;
+    anomalies_for(&state, norad_id, query).await
+}
+
+pub async fn iss_rollups(
+    Path(period): Path<String>,
+    State(state): State<AppState>,
+    Query(query): Query<IssRollupQuery>,
+) -> ApiResult<serde_json::Value> {
+    rollups_for(&state, ISS_NORAD_ID, &period, query).await
+}
+
+pub async fn satellite_rollups(
+    Path((norad_id, period)): Path<(i32, String)>,
+    State(state): State<AppState>,
+    Query(query): Query<IssRollupQuery>,
+) -> ApiResult<serde_json::Value> {
+    ensure_tracked(&state, norad_id)?;
+    rollups_for(&state, norad_id, &period, query).await
+}
+
+pub async fn iss_regions(
+    State(state): State<AppState>,
+    Query(query): Query<IssRegionsQuery>,
+) -> ApiResult<crate::domain::IssRegionTime> {
+    regions_for(&state, ISS_NORAD_ID, query).await
+}
+
+pub async fn satellite_regions(
+    Path(norad_id): Path<i32>,
+    State(state): State<AppState>,
+    Query(query): Query<IssRegionsQuery>,
+) -> ApiResult<crate::domain::IssRegionTime> {
+    ensure_tracked(&state, norad_id)?;
+    regions_for(&state, norad_id, query).await
+}
+
+pub async fn iss_sunlight(
+    State(state): State<AppState>,
+    Query(query): Query<IssSunlightQuery>,
+) -> ApiResult<crate::domain::IssSunlightReport> {
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query.from.unwrap_or(to - Duration::hours(3));
+    if from >= to {
+        return Err(ApiError::new("ISS_SUNLIGHT_BAD_RANGE", "`from` must be earlier than `to`"));
+    }
+    let report = state
+        .services
+        .iss
+        .sunlight(ISS_NORAD_ID, from, to)
+        .await
+        .map_err(|err| ApiError::from_error("ISS_SUNLIGHT_FAILED", err))?;
+    respond(report)
+}
+
+pub async fn iss_overflights(
+    State(state): State<AppState>,
+    Query(query): Query<IssOverflightsQuery>,
+) -> ApiResult<crate::domain::IssOverflights> {
+    overflights_for(&state, ISS_NORAD_ID, query).await
+}
+
+pub async fn satellite_overflights(
+    Path(norad_id): Path<i32>,
+    State(state): State<AppState>,
+    Query(query): Query<IssOverflightsQuery>,
+) -> ApiResult<crate::domain::IssOverflights> {
+    ensure_tracked(&state, norad_id)?;
+    overflights_for(&state, norad_id, query).await
+}
+
+pub async fn iss_orbit_events(
+    State(state): State<AppState>,
+    Query(query): Query<OrbitEventsQuery>,
+) -> ApiResult<serde_json::Value> {
+    orbit_events_for(&state, ISS_NORAD_ID, query).await
+}
+
+pub async fn satellite_orbit_events(
+    Path(norad_id): Path<i32>,
+    State(state): State<AppState>,
+    Query(query): Query<OrbitEventsQuery>,
+) -> ApiResult<serde_json::Value> {
+    ensure_tracked(&state, norad_id)?;
+    orbit_events_for(&state, norad_id, query).await
+}
+
+pub async fn iss_altitude_series(
+    State(state): State<AppState>,
+    Query(query): Query<AltitudeSeriesQuery>,
+) -> ApiResult<crate::domain::IssAltitudeSeries> {
+    altitude_series_for(&state, ISS_NORAD_ID, query).await
+}
+
+pub async fn satellite_altitude_series(
+    Path(norad_id): Path<i32>,
+    State(state): State<AppState>,
+    Query(query): Query<AltitudeSeriesQuery>,
+) -> ApiResult<crate::domain::IssAltitudeSeries> {
+    ensure_tracked(&state, norad_id)?;
+    altitude_series_for(&state, norad_id, query).await
+}
+
+pub async fn iss_orbit_run(State(state): State<AppState>) -> ApiResult<crate::domain::OrbitAnalysisReport> {
+    let report = state
+        .services
+        .orbit_analysis
+        .run()
+        .await
+        .map_err(|err| ApiError::from_error("ISS_ORBIT_ANALYSIS_FAILED", err))?;
+    respond(report)
+}
+
+pub async fn iss_rollup_run(State(state): State<AppState>) -> ApiResult<crate::domain::RollupReport> {
+    let report = state
+        .services
+        .rollup
+        .run()
+        .await
+        .map_err(|err| ApiError::from_error("ISS_ROLLUP_FAILED", err))?;
+    respond(report)
+}
+
+pub async fn geofences_list(State(state): State<AppState>) -> ApiResult<serde_json::Value> {
+    let items = state
+        .services
+        .geofence
+        .list()
+        .await
+        .map_err(|err| ApiError::from_error("DB_GEOFENCE_LIST_FAILED", err))?;
+    respond(json!({ "items": items }))
+}
+
+pub async fn geofence_create(
+    State(state): State<AppState>,
+    Json(body): Json<serde_json::Value>,
+) -> ApiResult<crate::domain::Geofence> {
+    let request: GeofenceCreateRequest =
+        serde_json::from_value(body).map_err(|err| ApiError::from_error("ISS_GEOFENCE_INVALID", err))?;
+    let fence = state
+        .services
+        .geofence
+        .create(&request.name, request.shape)
+        .await
+        .map_err(|err| ApiError::from_error("ISS_GEOFENCE_INVALID", err))?;
+    respond(fence)
+}
+
+pub async fn geofence_delete(Path(id): Path<i64>, State(state): State<AppState>) -> ApiResult<serde_json::Value> {
+    let deleted = state
+        .services
+        .geofence
+        .delete(id)
+        .await
+        .map_err(|err| ApiError::from_error("DB_GEOFENCE_DELETE_FAILED", err))?;
+    if !deleted {
+        return Err(ApiError::new("ISS_GEOFENCE_NOT_FOUND", format!("geofence {id} not found")));
+    }
+    respond(json!({ "deleted": id }))
+}
+
+pub async fn geofence_events(
+    Path(id): Path<i64>,
+    State(state): State<AppState>,
+    Query(query): Query<GeofenceEventsQuery>,
+) -> ApiResult<serde_json::Value> {
+    let svc = state.services.geofence.clone();
+    let fence = svc
+        .get(id)
+        .await
+        .map_err(|err| ApiError::from_error("DB_GEOFENCE_EVENTS_FAILED", err))?
+        .ok_or_else(|| ApiError::new("ISS_GEOFENCE_NOT_FOUND", format!("geofence {id} not found")))?;
+    let limit = query.limit.unwrap_or(100).clamp(1, 1_000);
+    let items = svc
+        .events(id, query.norad_id, query.since, limit)
+        .await
+        .map_err(|err| ApiError::from_error("DB_GEOFENCE_EVENTS_FAILED", err))?;
+    respond(json!({ "geofence": fence, "items": items }))
+}
+
+fn ensure_tracked(state: &AppState, norad_id: i32) -> Result<(), ApiError> {
+    state
+        .services
+        .iss
+        .ensure_tracked(norad_id)
+        .map_err(|err| ApiError::from_error("SATELLITE_NOT_TRACKED", err))
+}
+
+async fn last_for(state: &AppState, norad_id: i32) -> ApiResult<serde_json::Value> {
     let svc = state.services.iss.clone();
     let payload = svc
-        .last()
+        .last(norad_id)
         .await
         .map_err(|err| ApiError::from_error("DB_ISS_LAST_FAILED", err))?;
     if let Some(entry) = payload {
+        let region = entry.position.as_ref().map(|p| svc.region_of(p));
+        let sunlight = entry.position.as_ref().and_then(|p| {
+            let at = p.observed_at.unwrap_or(entry.fetched_at);
+            svc.sunlight_of(p, at)
+        });
         respond(json!({
             "id": entry.id,
+            "norad_id": entry.norad_id,
             "fetched_at": entry.fetched_at,
             "source_url": entry.source_url,
+            "position": entry.position,
+            "region": region,
+            "sunlight": sunlight,
+            "anomalies": entry.anomalies,
             "payload": entry.payload
         }))
     } else {
@@ -44,53 +514,403 @@ pub async fn last_iss(State(state): State<AppState>) -> ApiResult<serde_json::Va
     }
 }
 
-pub async fn trigger_iss(State(state): State<AppState>) -> ApiResult<serde_json::Value> {
-    let svc = state.services.iss.clone();
-    let entry = svc
-        .trigger_fetch()
+async fn anomalies_for(state: &AppState, norad_id: i32, query: IssAnomaliesQuery) -> ApiResult<serde_json::Value> {
+    let limit = query.limit.unwrap_or(50).clamp(1, 500);
+    let items = state
+        .services
+        .iss
+        .anomalies(norad_id, limit)
         .await
-        .map_err(|err| ApiError::from_error("ISS_FETCH_FAILED", err))?;
-    respond(json!({
-        "id": entry.id,
-        "fetched_at": entry.fetched_at,
-        "source_url": entry.source_url,
-        "payload": entry.payload
-    }))
+        .map_err(|err| ApiError::from_error("ISS_ANOMALIES_FAILED", err))?;
+    respond(json!({ "items": items }))
 }
 
-pub async fn iss_trend(State(state): State<AppState>) -> ApiResult<crate::domain::IssTrend> {
+async fn trend_for(state: &AppState, norad_id: i32, query: IssTrendQuery) -> ApiResult<crate::domain::IssTrend> {
+    let limit = query.limit.unwrap_or(2).clamp(2, 1_000);
     let svc = state.services.iss.clone();
     let trend = svc
-        .trend()
+        .trend(norad_id, limit, query.include_anomalies.unwrap_or(false))
         .await
         .map_err(|err| ApiError::from_error("ISS_TREND_FAILED", err))?;
     respond(trend)
 }
 
-pub async fn osdr_sync(State(state): State<AppState>) -> ApiResult<serde_json::Value> {
+async fn track_for(state: &AppState, norad_id: i32, query: IssTrackQuery) -> ApiResult<IssTrack> {
+    respond(load_track(state, norad_id, query).await?)
+}
+
+async fn regions_for(
+    state: &AppState,
+    norad_id: i32,
+    query: IssRegionsQuery,
+) -> ApiResult<crate::domain::IssRegionTime> {
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query.from.unwrap_or(to - Duration::hours(24));
+    if from >= to {
+        return Err(ApiError::new("ISS_REGIONS_BAD_RANGE", "`from` must be earlier than `to`"));
+    }
+    let max_gap = Duration::seconds(query.max_gap_sec.unwrap_or(600).clamp(60, 3_600));
+    let report = state
+        .services
+        .iss
+        .region_time(norad_id, from, to, max_gap)
+        .await
+        .map_err(|err| ApiError::from_error("ISS_REGIONS_FAILED", err))?;
+    respond(report)
+}
+
+async fn overflights_for(
+    state: &AppState,
+    norad_id: i32,
+    query: IssOverflightsQuery,
+) -> ApiResult<crate::domain::IssOverflights> {
+    let (Some(lat), Some(lon)) = (query.lat, query.lon) else {
+        return Err(ApiError::new("ISS_OVERFLIGHTS_BAD_QUERY", "`lat` and `lon` are required"));
+    };
+    if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lon) {
+        return Err(ApiError::new("ISS_OVERFLIGHTS_BAD_QUERY", "`lat`/`lon` out of range"));
+    }
+    let radius_km = query.radius_km.unwrap_or(500.0).clamp(1.0, 5_000.0);
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query.from.unwrap_or(to - Duration::days(7));
+    if from >= to {
+        return Err(ApiError::new("ISS_OVERFLIGHTS_BAD_RANGE", "`from` must be earlier than `to`"));
+    }
+    let max_gap = Duration::seconds(query.max_gap_sec.unwrap_or(300).clamp(30, 1_800));
+    let report = state
+        .services
+        .iss
+        .overflights(norad_id, (lat, lon), radius_km, from, to, max_gap)
+        .await
+        .map_err(|err| ApiError::from_error("ISS_OVERFLIGHTS_FAILED", err))?;
+    respond(report)
+}
+
+async fn orbit_events_for(state: &AppState, norad_id: i32, query: OrbitEventsQuery) -> ApiResult<serde_json::Value> {
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query.from.unwrap_or(to - Duration::days(180));
+    if from >= to {
+        return Err(ApiError::new("ISS_ORBIT_BAD_RANGE", "`from` must be earlier than `to`"));
+    }
+    let limit = query.limit.unwrap_or(100).clamp(1, 1_000);
+    let items = state
+        .services
+        .orbit_analysis
+        .events(norad_id, from, to, limit)
+        .await
+        .map_err(|err| ApiError::from_error("ISS_ORBIT_EVENTS_FAILED", err))?;
+    respond(json!({ "from": from, "to": to, "items": items }))
+}
+
+async fn altitude_series_for(
+    state: &AppState,
+    norad_id: i32,
+    query: AltitudeSeriesQuery,
+) -> ApiResult<crate::domain::IssAltitudeSeries> {
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query.from.unwrap_or(to - Duration::days(14));
+    if from >= to {
+        return Err(ApiError::new("ISS_ORBIT_BAD_RANGE", "`from` must be earlier than `to`"));
+    }
+    // The default bucket is one ISS orbit; other satellites need their own period.
+    let bucket_sec = match query.bucket_minutes {
+        Some(minutes) => minutes.clamp(10, 1_440) * 60,
+        None if norad_id == ISS_NORAD_ID => ORBIT_BUCKET_SEC,
+        None => {
+            return Err(ApiError::new(
+                "ISS_ORBIT_BAD_BUCKET",
+                "`bucket_minutes` is required for satellites other than the ISS",
+            ))
+        }
+    };
+    let window = Duration::hours(query.window_hours.unwrap_or(72).clamp(6, 720));
+    let series = state
+        .services
+        .orbit_analysis
+        .altitude_series(norad_id, from, to, bucket_sec, window)
+        .await
+        .map_err(|err| ApiError::from_error("ISS_ORBIT_SERIES_FAILED", err))?;
+    respond(series)
+}
+
+async fn rollups_for(
+    state: &AppState,
+    norad_id: i32,
+    period: &str,
+    query: IssRollupQuery,
+) -> ApiResult<serde_json::Value> {
+    let period = RollupPeriod::parse(period)
+        .ok_or_else(|| ApiError::new("ISS_ROLLUP_BAD_PERIOD", "period must be `hourly` or `daily`"))?;
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query.from.unwrap_or_else(|| match period {
+        RollupPeriod::Hourly => to - Duration::days(2),
+        RollupPeriod::Daily => to - Duration::days(90),
+    });
+    if from >= to {
+        return Err(ApiError::new("ISS_ROLLUP_BAD_RANGE", "`from` must be earlier than `to`"));
+    }
+    let items = state
+        .services
+        .rollup
+        .list(period, norad_id, from, to)
+        .await
+        .map_err(|err| ApiError::from_error("DB_ISS_ROLLUP_FAILED", err))?;
+    respond(json!({ "norad_id": norad_id, "period": period.unit(), "from": from, "to": to, "items": items }))
+}
+
+/// Upper bound on `(to - from) / fill_sec` for gap-filled tracks.
+const MAX_FILLED_TRACK_STEPS: i64 = 50_000;
+
+async fn load_track(state: &AppState, norad_id: i32, query: IssTrackQuery) -> Result<IssTrack, ApiError> {
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query.from.unwrap_or(to - Duration::hours(24));
+    if from >= to {
+        return Err(ApiError::new("ISS_TRACK_BAD_RANGE", "`from` must be earlier than `to`"));
+    }
+    let max_points = query.max_points.unwrap_or(500).clamp(2, 5_000);
+    let fill_step = query.fill_sec.map(|sec| Duration::seconds(sec.clamp(10, 3_600)));
+    if let Some(step) = fill_step {
+        // Gap filling propagates every step before downsampling, so bound the work up front.
+        if (to - from).num_seconds() / step.num_seconds() > MAX_FILLED_TRACK_STEPS {
+            return Err(ApiError::new(
+                "ISS_TRACK_BAD_RANGE",
+                format!("window too long for fill_sec: at most {MAX_FILLED_TRACK_STEPS} steps, use a larger step"),
+            ));
+        }
+    }
+    state
+        .services
+        .iss
+        .track(
+            norad_id,
+            from,
+            to,
+            max_points,
+            fill_step,
+            query.include_anomalies.unwrap_or(false),
+        )
+        .await
+        .map_err(|err| ApiError::from_error("ISS_TRACK_FAILED", err))
+}
+
+pub async fn iss_position(
+    State(state): State<AppState>,
+    Query(query): Query<IssPositionQuery>,
+) -> ApiResult<crate::domain::PredictedPosition> {
+    let at = query.at.unwrap_or_else(Utc::now);
+    let position = state
+        .services
+        .orbit
+        .position_at(at)
+        .await
+        .map_err(|err| ApiError::from_error("ISS_POSITION_FAILED", err))?;
+    respond(position)
+}
+
+pub async fn iss_at(
+    State(state): State<AppState>,
+    Query(query): Query<IssAtQuery>,
+) -> ApiResult<crate::domain::IssInterpolatedPosition> {
+    let ts = query
+        .ts
+        .ok_or_else(|| ApiError::new("ISS_AT_BAD_TS", "`ts` is required (RFC 3339)"))?;
+    let max_gap = Duration::seconds(query.max_gap_sec.unwrap_or(300).clamp(10, 3_600));
+    let position = state
+        .services
+        .iss
+        .position_at(ISS_NORAD_ID, ts, max_gap)
+        .await
+        .map_err(|err| ApiError::from_error("ISS_AT_FAILED", err))?
+        .ok_or_else(|| ApiError::new("ISS_AT_OUT_OF_RANGE", format!("no stored samples around {ts}")))?;
+    respond(position)
+}
+
+pub async fn iss_passes(
+    State(state): State<AppState>,
+    Query(query): Query<IssPassesQuery>,
+) -> ApiResult<crate::domain::IssPassPrediction> {
+    let (Some(lat), Some(lon)) = (query.lat, query.lon) else {
+        return Err(ApiError::new("ISS_PASSES_BAD_QUERY", "`lat` and `lon` are required"));
+    };
+    if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lon) {
+        return Err(ApiError::new("ISS_PASSES_BAD_QUERY", "`lat`/`lon` out of range"));
+    }
+    let observer = Observer {
+        lat,
+        lon,
+        alt_m: query.alt.unwrap_or(0.0),
+    };
+    let from = Utc::now();
+    let to = from + Duration::days(query.days.unwrap_or(3).clamp(1, 10));
+    let prediction = state
+        .services
+        .orbit
+        .passes(observer, from, to)
+        .await
+        .map_err(|err| ApiError::from_error("ISS_PASSES_FAILED", err))?;
+    respond(prediction)
+}
+
+pub async fn osdr_sync(
+    State(state): State<AppState>,
+    Query(query): Query<OsdrSyncQuery>,
+) -> ApiResult<crate::domain::OsdrSyncReport> {
     let svc = state.services.osdr.clone();
-    let written = svc
-        .sync()
+    let report = svc
+        .sync(query.full.unwrap_or(false))
         .await
         .map_err(|err| ApiError::from_error("OSDR_SYNC_FAILED", err))?;
-    respond(json!({ "written": written }))
+    respond(report)
 }
 
 pub async fn osdr_list(
     State(state): State<AppState>,
     Query(query): Query<OsdrListQuery>,
-) -> ApiResult<serde_json::Value> {
+) -> ApiResult<crate::domain::OsdrListPage> {
     let limit = query
         .limit
         .unwrap_or(state.config.osdr_list_limit)
         .clamp(1, 200);
-    let items = state
+    let sort = match query.sort.as_deref() {
+        None => OsdrSortKey::InsertedAt,
+        Some(raw) => OsdrSortKey::parse(raw).ok_or_else(|| {
+            ApiError::new(
+                "OSDR_LIST_BAD_QUERY",
+                "`sort` must be one of inserted_at, updated_at, dataset_id, title",
+            )
+        })?,
+    };
+    let removed = removed_filter(query.removed.as_deref(), "OSDR_LIST_BAD_QUERY")?;
+    let descending = match query.order.as_deref() {
+        None | Some("desc") => true,
+        Some("asc") => false,
+        Some(_) => return Err(ApiError::new("OSDR_LIST_BAD_QUERY", "`order` must be asc or desc")),
+    };
+    if let (Some(from), Some(to)) = (query.updated_from, query.updated_to) {
+        if from > to {
+            return Err(ApiError::new("OSDR_LIST_BAD_RANGE", "`updated_from` must not be after `updated_to`"));
+        }
+    }
+    let cursor = match query.cursor.as_deref().filter(|raw| !raw.is_empty()) {
+        None => None,
+        Some(raw) => {
+            let cursor = osdr::decode_cursor(raw)
+                .ok_or_else(|| ApiError::new("OSDR_LIST_BAD_CURSOR", "`cursor` is not a valid list cursor"))?;
+            let resorted = query.sort.is_some() && cursor.sort != sort;
+            let reordered = query.order.is_some() && cursor.descending != descending;
+            if resorted || reordered {
+                return Err(ApiError::new(
+                    "OSDR_LIST_BAD_CURSOR",
+                    "`cursor` was issued for a different sort order",
+                ));
+            }
+            Some(cursor)
+        }
+    };
+    let filter = OsdrListFilter {
+        removed,
+        status: query.status.filter(|status| !status.is_empty()),
+        updated_from: query.updated_from,
+        updated_to: query.updated_to,
+        id_prefix: query.id_prefix.filter(|prefix| !prefix.is_empty()),
+    };
+    let page = state
         .services
         .osdr
-        .list(limit)
+        .list(&filter, sort, descending, cursor.as_ref(), limit)
         .await
         .map_err(|err| ApiError::from_error("OSDR_LIST_FAILED", err))?;
-    respond(json!({ "items": items }))
+    respond(page)
+}
+
+pub async fn osdr_search(
+    State(state): State<AppState>,
+    Query(query): Query<OsdrSearchQuery>,
+) -> ApiResult<crate::domain::OsdrSearchPage> {
+    let q = osdr::search_terms(query.q.as_deref()).map_err(|err| ApiError::from_error("OSDR_SEARCH_BAD_QUERY", err))?;
+    let (limit, offset) = osdr::search_paging(query.limit, query.offset, state.config.osdr_list_limit);
+    let removed = removed_filter(query.removed.as_deref(), "OSDR_SEARCH_BAD_QUERY")?;
+    let page = state
+        .services
+        .osdr
+        .search(q, removed, limit, offset)
+        .await
+        .map_err(|err| ApiError::from_error("OSDR_SEARCH_FAILED", err))?;
+    respond(page)
+}
+
+/// `removed` query parameter shared by the OSDR read endpoints; tombstoned datasets are
+/// hidden unless asked for.
+fn removed_filter(raw: Option<&str>, code: &'static str) -> Result<OsdrRemovedFilter, ApiError> {
+    raw.map_or(Ok(OsdrRemovedFilter::Exclude), |raw| {
+        OsdrRemovedFilter::parse(raw).ok_or_else(|| ApiError::new(code, "`removed` must be exclude, include or only"))
+    })
+}
+
+pub async fn osdr_mapping_dry_run(
+    State(state): State<AppState>,
+    Json(body): Json<serde_json::Value>,
+) -> ApiResult<crate::domain::OsdrMappingPreview> {
+    let request: OsdrMappingDryRunRequest =
+        serde_json::from_value(body).map_err(|err| ApiError::from_error("OSDR_MAPPING_INVALID", err))?;
+    let preview = state
+        .services
+        .osdr
+        .dry_run(request.source, request.mapping, request.payload)
+        .map_err(|err| ApiError::new("OSDR_MAPPING_INVALID", format!("{err:#}")))?;
+    respond(preview)
+}
+
+pub async fn osdr_dataset(
+    Path(dataset_id): Path<String>,
+    State(state): State<AppState>,
+    Query(query): Query<OsdrDatasetQuery>,
+) -> ApiResult<crate::domain::OsdrRecord> {
+    let removed = removed_filter(query.removed.as_deref(), "OSDR_DATASET_BAD_QUERY")?;
+    let dataset = state
+        .services
+        .osdr
+        .get(&dataset_id, removed)
+        .await
+        .map_err(|err| ApiError::from_error("OSDR_DATASET_FAILED", err))?
+        .ok_or_else(|| ApiError::new("OSDR_DATASET_NOT_FOUND", format!("dataset {dataset_id} not found")))?;
+    respond(dataset)
+}
+
+pub async fn osdr_files(
+    Path(dataset_id): Path<String>,
+    State(state): State<AppState>,
+    Query(query): Query<OsdrFilesQuery>,
+) -> ApiResult<crate::domain::OsdrFileListing> {
+    let removed = removed_filter(query.removed.as_deref(), "OSDR_FILES_BAD_QUERY")?;
+    let listing = state
+        .services
+        .osdr
+        .files(&dataset_id, removed, query.refresh.unwrap_or(false))
+        .await
+        .map_err(|err| ApiError::from_error("OSDR_FILES_FAILED", err))?
+        .ok_or_else(|| ApiError::new("OSDR_DATASET_NOT_FOUND", format!("dataset {dataset_id} not found")))?;
+    respond(listing)
+}
+
+pub async fn osdr_history(
+    Path(dataset_id): Path<String>,
+    State(state): State<AppState>,
+    Query(query): Query<OsdrHistoryQuery>,
+) -> ApiResult<serde_json::Value> {
+    let svc = state.services.osdr.clone();
+    // History stays readable after removal; its last revision records the tombstone.
+    let dataset = svc
+        .get(&dataset_id, OsdrRemovedFilter::Include)
+        .await
+        .map_err(|err| ApiError::from_error("OSDR_HISTORY_FAILED", err))?
+        .ok_or_else(|| ApiError::new("OSDR_DATASET_NOT_FOUND", format!("dataset {dataset_id} not found")))?;
+    let limit = query.limit.unwrap_or(100).clamp(1, 1_000);
+    let items = svc
+        .history(&dataset_id, query.since, limit)
+        .await
+        .map_err(|err| ApiError::from_error("OSDR_HISTORY_FAILED", err))?;
+    respond(json!({ "dataset": dataset, "items": items }))
 }
 
 pub async fn space_latest(
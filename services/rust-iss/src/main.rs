@@ -2,13 +2,22 @@ mod clients;
 mod config;
 mod domain;
 mod error;
+mod export;
+mod geo;
 mod handlers;
+mod orbit;
+mod osdr;
 mod repo;
 mod routes;
 mod services;
+mod solar;
+
+use std::sync::Arc;
 
 use anyhow::Context;
 use config::AppConfig;
+use geo::ReverseGeocoder;
+use osdr::FieldMap;
 use repo::Repositories;
 use services::{spawn_jobs, AppState, ServiceRegistry};
 use sqlx::postgres::PgPoolOptions;
@@ -35,7 +44,11 @@ async fn main() -> anyhow::Result<()> {
     repos.migrate().await?;
 
     let clients = ExternalClients::new(&config)?;
-    let services = ServiceRegistry::new(&repos, &clients, &config);
+    let geocoder = ReverseGeocoder::load(config.geo_boundaries_path.as_deref())?;
+    info!("reverse geocoder loaded {} boundaries", geocoder.boundary_count());
+    let field_map = FieldMap::load(config.osdr_field_map_path.as_deref())?;
+    info!("osdr field map loaded with {} source profiles", field_map.source_count());
+    let services = ServiceRegistry::new(&repos, &clients, Arc::new(geocoder), Arc::new(field_map), &config);
     let state = AppState::new(config.clone(), services);
 
     spawn_jobs(&state);
@@ -0,0 +1,614 @@
+use std::f64::consts::TAU;
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+
+// WGS-72 constants, as required by SGP4.
+const RE_KM: f64 = 6378.135;
+const XKE: f64 = 0.074_366_916_133_173_4;
+const J2: f64 = 0.001_082_616;
+const J3: f64 = -0.000_002_538_81;
+const J4: f64 = -0.000_001_655_97;
+const J3OJ2: f64 = J3 / J2;
+const X2O3: f64 = 2.0 / 3.0;
+
+// WGS-84 ellipsoid for geodetic output.
+const WGS84_A_KM: f64 = 6378.137;
+const WGS84_F: f64 = 1.0 / 298.257_223_563;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tle {
+    pub name: Option<String>,
+    pub line1: String,
+    pub line2: String,
+}
+
+impl Tle {
+    /// Picks the first two-line element set out of a text blob (2LE or 3LE format).
+    pub fn parse(text: &str) -> Result<Self> {
+        let lines: Vec<&str> = text.lines().map(str::trim_end).filter(|l| !l.trim().is_empty()).collect();
+        let idx = lines
+            .windows(2)
+            .position(|pair| pair[0].starts_with("1 ") && pair[1].starts_with("2 "))
+            .ok_or_else(|| anyhow!("no two-line element set found"))?;
+        let name = idx
+            .checked_sub(1)
+            .map(|i| lines[i].trim_start_matches("0 ").trim().to_string())
+            .filter(|n| !n.is_empty());
+        Ok(Self {
+            name,
+            line1: lines[idx].to_string(),
+            line2: lines[idx + 1].to_string(),
+        })
+    }
+}
+
+/// Position and velocity in the TEME frame (km, km/s).
+#[derive(Debug, Clone, Copy)]
+pub struct TemeState {
+    pub position: [f64; 3],
+    pub velocity: [f64; 3],
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Geodetic {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude_km: f64,
+}
+
+/// Near-Earth SGP4 propagator (Vallado et al., "Revisiting Spacetrack Report #3").
+/// Deep-space objects (period >= 225 min) are rejected; everything we track is in LEO.
+#[derive(Debug, Clone)]
+pub struct Sgp4 {
+    pub epoch: DateTime<Utc>,
+    bstar: f64,
+    ecco: f64,
+    inclo: f64,
+    nodeo: f64,
+    argpo: f64,
+    mo: f64,
+    no: f64,
+    isimp: bool,
+    con41: f64,
+    x1mth2: f64,
+    x7thm1: f64,
+    cc1: f64,
+    cc4: f64,
+    cc5: f64,
+    d2: f64,
+    d3: f64,
+    d4: f64,
+    delmo: f64,
+    eta: f64,
+    argpdot: f64,
+    omgcof: f64,
+    sinmao: f64,
+    t2cof: f64,
+    t3cof: f64,
+    t4cof: f64,
+    t5cof: f64,
+    xlcof: f64,
+    aycof: f64,
+    mdot: f64,
+    nodedot: f64,
+    nodecf: f64,
+    xmcof: f64,
+}
+
+impl Sgp4 {
+    pub fn from_tle(tle: &Tle) -> Result<Self> {
+        let l1 = &tle.line1;
+        let l2 = &tle.line2;
+
+        let year: i32 = field(l1, 18, 20)?.trim().parse().context("epoch year")?;
+        let year = if year < 57 { 2000 + year } else { 1900 + year };
+        let day: f64 = field(l1, 20, 32)?.trim().parse().context("epoch day")?;
+        let bstar = parse_implied_exponent(field(l1, 53, 61)?).context("bstar")?;
+
+        let inclo = parse_f64(l2, 8, 16, "inclination")?.to_radians();
+        let nodeo = parse_f64(l2, 17, 25, "raan")?.to_radians();
+        let ecco: f64 = format!("0.{}", field(l2, 26, 33)?.trim()).parse().context("eccentricity")?;
+        let argpo = parse_f64(l2, 34, 42, "argument of perigee")?.to_radians();
+        let mo = parse_f64(l2, 43, 51, "mean anomaly")?.to_radians();
+        let mean_motion = parse_f64(l2, 52, 63, "mean motion")?;
+
+        let jan1 = Utc.from_utc_datetime(
+            &NaiveDate::from_ymd_opt(year, 1, 1)
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                .ok_or_else(|| anyhow!("invalid epoch year {year}"))?,
+        );
+        let epoch = jan1 + Duration::microseconds(((day - 1.0) * 86_400_000_000.0).round() as i64);
+
+        Self::init(epoch, bstar, ecco, inclo, nodeo, argpo, mo, mean_motion * TAU / 1_440.0)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn init(
+        epoch: DateTime<Utc>,
+        bstar: f64,
+        ecco: f64,
+        inclo: f64,
+        nodeo: f64,
+        argpo: f64,
+        mo: f64,
+        no_kozai: f64,
+    ) -> Result<Self> {
+        // Recover the original (un-Kozai) mean motion and semi-major axis.
+        let eccsq = ecco * ecco;
+        let omeosq = 1.0 - eccsq;
+        let rteosq = omeosq.sqrt();
+        let cosio = inclo.cos();
+        let cosio2 = cosio * cosio;
+        let ak = (XKE / no_kozai).powf(X2O3);
+        let d1 = 0.75 * J2 * (3.0 * cosio2 - 1.0) / (rteosq * omeosq);
+        let mut del = d1 / (ak * ak);
+        let adel = ak * (1.0 - del * del - del * (1.0 / 3.0 + 134.0 * del * del / 81.0));
+        del = d1 / (adel * adel);
+        let no = no_kozai / (1.0 + del);
+        let ao = (XKE / no).powf(X2O3);
+        let sinio = inclo.sin();
+        let po = ao * omeosq;
+        let con42 = 1.0 - 5.0 * cosio2;
+        let con41 = -con42 - cosio2 - cosio2;
+        let posq = po * po;
+        let rp = ao * (1.0 - ecco);
+
+        if TAU / no >= 225.0 {
+            bail!("deep-space orbits are not supported");
+        }
+
+        let ss = 78.0 / RE_KM + 1.0;
+        let qzms2t = ((120.0 - 78.0) / RE_KM).powi(4);
+        let isimp = rp < 220.0 / RE_KM + 1.0;
+        let mut sfour = ss;
+        let mut qzms24 = qzms2t;
+        let perige = (rp - 1.0) * RE_KM;
+        if perige < 156.0 {
+            sfour = if perige < 98.0 { 20.0 } else { perige - 78.0 };
+            qzms24 = ((120.0 - sfour) / RE_KM).powi(4);
+            sfour = sfour / RE_KM + 1.0;
+        }
+        let pinvsq = 1.0 / posq;
+        let tsi = 1.0 / (ao - sfour);
+        let eta = ao * ecco * tsi;
+        let etasq = eta * eta;
+        let eeta = ecco * eta;
+        let psisq = (1.0 - etasq).abs();
+        let coef = qzms24 * tsi.powi(4);
+        let coef1 = coef / psisq.powf(3.5);
+        let cc2 = coef1
+            * no
+            * (ao * (1.0 + 1.5 * etasq + eeta * (4.0 + etasq))
+                + 0.375 * J2 * tsi / psisq * con41 * (8.0 + 3.0 * etasq * (8.0 + etasq)));
+        let cc1 = bstar * cc2;
+        let cc3 = if ecco > 1.0e-4 {
+            -2.0 * coef * tsi * J3OJ2 * no * sinio / ecco
+        } else {
+            0.0
+        };
+        let x1mth2 = 1.0 - cosio2;
+        let cc4 = 2.0
+            * no
+            * coef1
+            * ao
+            * omeosq
+            * (eta * (2.0 + 0.5 * etasq) + ecco * (0.5 + 2.0 * etasq)
+                - J2 * tsi / (ao * psisq)
+                    * (-3.0 * con41 * (1.0 - 2.0 * eeta + etasq * (1.5 - 0.5 * eeta))
+                        + 0.75 * x1mth2 * (2.0 * etasq - eeta * (1.0 + etasq)) * (2.0 * argpo).cos()));
+        let cc5 = 2.0 * coef1 * ao * omeosq * (1.0 + 2.75 * (etasq + eeta) + eeta * etasq);
+        let cosio4 = cosio2 * cosio2;
+        let temp1 = 1.5 * J2 * pinvsq * no;
+        let temp2 = 0.5 * temp1 * J2 * pinvsq;
+        let temp3 = -0.46875 * J4 * pinvsq * pinvsq * no;
+        let mdot = no
+            + 0.5 * temp1 * rteosq * con41
+            + 0.0625 * temp2 * rteosq * (13.0 - 78.0 * cosio2 + 137.0 * cosio4);
+        let argpdot = -0.5 * temp1 * con42
+            + 0.0625 * temp2 * (7.0 - 114.0 * cosio2 + 395.0 * cosio4)
+            + temp3 * (3.0 - 36.0 * cosio2 + 49.0 * cosio4);
+        let xhdot1 = -temp1 * cosio;
+        let nodedot = xhdot1 + (0.5 * temp2 * (4.0 - 19.0 * cosio2) + 2.0 * temp3 * (3.0 - 7.0 * cosio2)) * cosio;
+        let omgcof = bstar * cc3 * argpo.cos();
+        let xmcof = if ecco > 1.0e-4 { -X2O3 * coef * bstar / eeta } else { 0.0 };
+        let nodecf = 3.5 * omeosq * xhdot1 * cc1;
+        let t2cof = 1.5 * cc1;
+        let xlcof_den = if (cosio + 1.0).abs() > 1.5e-12 { 1.0 + cosio } else { 1.5e-12 };
+        let xlcof = -0.25 * J3OJ2 * sinio * (3.0 + 5.0 * cosio) / xlcof_den;
+        let aycof = -0.5 * J3OJ2 * sinio;
+        let delmo = (1.0 + eta * mo.cos()).powi(3);
+        let sinmao = mo.sin();
+        let x7thm1 = 7.0 * cosio2 - 1.0;
+
+        let (mut d2, mut d3, mut d4, mut t3cof, mut t4cof, mut t5cof) = (0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+        if !isimp {
+            let cc1sq = cc1 * cc1;
+            d2 = 4.0 * ao * tsi * cc1sq;
+            let temp = d2 * tsi * cc1 / 3.0;
+            d3 = (17.0 * ao + sfour) * temp;
+            d4 = 0.5 * temp * ao * tsi * (221.0 * ao + 31.0 * sfour) * cc1;
+            t3cof = d2 + 2.0 * cc1sq;
+            t4cof = 0.25 * (3.0 * d3 + cc1 * (12.0 * d2 + 10.0 * cc1sq));
+            t5cof = 0.2 * (3.0 * d4 + 12.0 * cc1 * d3 + 6.0 * d2 * d2 + 15.0 * cc1sq * (2.0 * d2 + cc1sq));
+        }
+
+        Ok(Self {
+            epoch,
+            bstar,
+            ecco,
+            inclo,
+            nodeo,
+            argpo,
+            mo,
+            no,
+            isimp,
+            con41,
+            x1mth2,
+            x7thm1,
+            cc1,
+            cc4,
+            cc5,
+            d2,
+            d3,
+            d4,
+            delmo,
+            eta,
+            argpdot,
+            omgcof,
+            sinmao,
+            t2cof,
+            t3cof,
+            t4cof,
+            t5cof,
+            xlcof,
+            aycof,
+            mdot,
+            nodedot,
+            nodecf,
+            xmcof,
+        })
+    }
+
+    pub fn propagate(&self, at: DateTime<Utc>) -> Result<TemeState> {
+        let minutes = (at - self.epoch).num_milliseconds() as f64 / 60_000.0;
+        self.propagate_minutes(minutes)
+    }
+
+    pub fn propagate_minutes(&self, t: f64) -> Result<TemeState> {
+        let xmdf = self.mo + self.mdot * t;
+        let argpdf = self.argpo + self.argpdot * t;
+        let nodedf = self.nodeo + self.nodedot * t;
+        let mut argpm = argpdf;
+        let mut mm = xmdf;
+        let t2 = t * t;
+        let mut nodem = nodedf + self.nodecf * t2;
+        let mut tempa = 1.0 - self.cc1 * t;
+        let mut tempe = self.bstar * self.cc4 * t;
+        let mut templ = self.t2cof * t2;
+
+        if !self.isimp {
+            let delomg = self.omgcof * t;
+            let delm = self.xmcof * ((1.0 + self.eta * xmdf.cos()).powi(3) - self.delmo);
+            let temp = delomg + delm;
+            mm = xmdf + temp;
+            argpm = argpdf - temp;
+            let t3 = t2 * t;
+            let t4 = t3 * t;
+            tempa = tempa - self.d2 * t2 - self.d3 * t3 - self.d4 * t4;
+            tempe += self.bstar * self.cc5 * (mm.sin() - self.sinmao);
+            templ += self.t3cof * t3 + t4 * (self.t4cof + t * self.t5cof);
+        }
+
+        let am = (XKE / self.no).powf(X2O3) * tempa * tempa;
+        let nm = XKE / am.powf(1.5);
+        let mut em = self.ecco - tempe;
+        if !(-0.001..1.0).contains(&em) {
+            bail!("eccentricity out of range after {t:.1} min");
+        }
+        if em < 1.0e-6 {
+            em = 1.0e-6;
+        }
+        mm += self.no * templ;
+        let xlm = (mm + argpm + nodem) % TAU;
+        nodem %= TAU;
+        argpm %= TAU;
+        mm = (xlm - argpm - nodem) % TAU;
+
+        let sinip = self.inclo.sin();
+        let cosip = self.inclo.cos();
+
+        // Long-period periodics.
+        let axnl = em * argpm.cos();
+        let temp = 1.0 / (am * (1.0 - em * em));
+        let aynl = em * argpm.sin() + temp * self.aycof;
+        let xl = mm + argpm + nodem + temp * self.xlcof * axnl;
+
+        // Kepler's equation.
+        let u = (xl - nodem) % TAU;
+        let mut eo1 = u;
+        let mut tem5: f64 = 9999.9;
+        let mut ktr = 1;
+        let (mut sineo1, mut coseo1) = (0.0, 0.0);
+        while tem5.abs() >= 1.0e-12 && ktr <= 10 {
+            sineo1 = eo1.sin();
+            coseo1 = eo1.cos();
+            tem5 = 1.0 - coseo1 * axnl - sineo1 * aynl;
+            tem5 = (u - aynl * coseo1 + axnl * sineo1 - eo1) / tem5;
+            if tem5.abs() >= 0.95 {
+                tem5 = 0.95 * tem5.signum();
+            }
+            eo1 += tem5;
+            ktr += 1;
+        }
+
+        // Short-period periodics.
+        let ecose = axnl * coseo1 + aynl * sineo1;
+        let esine = axnl * sineo1 - aynl * coseo1;
+        let el2 = axnl * axnl + aynl * aynl;
+        let pl = am * (1.0 - el2);
+        if pl < 0.0 {
+            bail!("semi-latus rectum negative after {t:.1} min");
+        }
+        let rl = am * (1.0 - ecose);
+        let rdotl = am.sqrt() * esine / rl;
+        let rvdotl = pl.sqrt() / rl;
+        let betal = (1.0 - el2).sqrt();
+        let temp = esine / (1.0 + betal);
+        let sinu = am / rl * (sineo1 - aynl - axnl * temp);
+        let cosu = am / rl * (coseo1 - axnl + aynl * temp);
+        let mut su = sinu.atan2(cosu);
+        let sin2u = (cosu + cosu) * sinu;
+        let cos2u = 1.0 - 2.0 * sinu * sinu;
+        let temp = 1.0 / pl;
+        let temp1 = 0.5 * J2 * temp;
+        let temp2 = temp1 * temp;
+
+        let mrt = rl * (1.0 - 1.5 * temp2 * betal * self.con41) + 0.5 * temp1 * self.x1mth2 * cos2u;
+        su -= 0.25 * temp2 * self.x7thm1 * sin2u;
+        let xnode = nodem + 1.5 * temp2 * cosip * sin2u;
+        let xinc = self.inclo + 1.5 * temp2 * cosip * sinip * cos2u;
+        let mvt = rdotl - nm * temp1 * self.x1mth2 * sin2u / XKE;
+        let rvdot = rvdotl + nm * temp1 * (self.x1mth2 * cos2u + 1.5 * self.con41) / XKE;
+
+        if mrt < 1.0 {
+            bail!("satellite has decayed after {t:.1} min");
+        }
+
+        let (sinsu, cossu) = su.sin_cos();
+        let (snod, cnod) = xnode.sin_cos();
+        let (sini, cosi) = xinc.sin_cos();
+        let xmx = -snod * cosi;
+        let xmy = cnod * cosi;
+        let ux = xmx * sinsu + cnod * cossu;
+        let uy = xmy * sinsu + snod * cossu;
+        let uz = sini * sinsu;
+        let vx = xmx * cossu - cnod * sinsu;
+        let vy = xmy * cossu - snod * sinsu;
+        let vz = sini * cossu;
+
+        let vkmpersec = RE_KM * XKE / 60.0;
+        Ok(TemeState {
+            position: [mrt * ux * RE_KM, mrt * uy * RE_KM, mrt * uz * RE_KM],
+            velocity: [
+                (mvt * ux + rvdot * vx) * vkmpersec,
+                (mvt * uy + rvdot * vy) * vkmpersec,
+                (mvt * uz + rvdot * vz) * vkmpersec,
+            ],
+        })
+    }
+
+}
+
+impl TemeState {
+    pub fn speed_kmh(&self) -> f64 {
+        norm(self.velocity) * 3_600.0
+    }
+
+    pub fn ecef(&self, at: DateTime<Utc>) -> [f64; 3] {
+        teme_to_ecef(self.position, at)
+    }
+
+    pub fn geodetic(&self, at: DateTime<Utc>) -> Geodetic {
+        ecef_to_geodetic(self.ecef(at))
+    }
+}
+
+pub fn julian_date(at: DateTime<Utc>) -> f64 {
+    at.timestamp_millis() as f64 / 86_400_000.0 + 2_440_587.5
+}
+
+/// Greenwich mean sidereal time in radians (IAU-82, as used by SGP4).
+pub fn gmst(at: DateTime<Utc>) -> f64 {
+    let tut1 = (julian_date(at) - 2_451_545.0) / 36_525.0;
+    let seconds = -6.2e-6 * tut1.powi(3)
+        + 0.093_104 * tut1 * tut1
+        + (876_600.0 * 3_600.0 + 8_640_184.812_866) * tut1
+        + 67_310.548_41;
+    (seconds.to_radians() / 240.0).rem_euclid(TAU)
+}
+
+/// Rotates a TEME vector into the Earth-fixed frame (polar motion ignored).
+pub fn teme_to_ecef(r: [f64; 3], at: DateTime<Utc>) -> [f64; 3] {
+    let (s, c) = gmst(at).sin_cos();
+    let [x, y, z] = r;
+    [c * x + s * y, -s * x + c * y, z]
+}
+
+pub fn ecef_to_geodetic(r: [f64; 3]) -> Geodetic {
+    let [x, y, z] = r;
+    let e2 = WGS84_F * (2.0 - WGS84_F);
+    let p = (x * x + y * y).sqrt();
+    let mut lat = z.atan2(p * (1.0 - e2));
+    let mut alt = 0.0;
+    for _ in 0..6 {
+        let sin_lat = lat.sin();
+        let n = WGS84_A_KM / (1.0 - e2 * sin_lat * sin_lat).sqrt();
+        alt = if lat.cos().abs() > 1e-9 { p / lat.cos() - n } else { z.abs() - n * (1.0 - e2) };
+        lat = z.atan2(p * (1.0 - e2 * n / (n + alt)));
+    }
+    Geodetic {
+        latitude: lat.to_degrees(),
+        longitude: normalize_lon(y.atan2(x).to_degrees()),
+        altitude_km: alt,
+    }
+}
+
+pub fn geodetic_to_ecef(lat_deg: f64, lon_deg: f64, alt_km: f64) -> [f64; 3] {
+    let e2 = WGS84_F * (2.0 - WGS84_F);
+    let (sin_lat, cos_lat) = lat_deg.to_radians().sin_cos();
+    let (sin_lon, cos_lon) = lon_deg.to_radians().sin_cos();
+    let n = WGS84_A_KM / (1.0 - e2 * sin_lat * sin_lat).sqrt();
+    [
+        (n + alt_km) * cos_lat * cos_lon,
+        (n + alt_km) * cos_lat * sin_lon,
+        (n * (1.0 - e2) + alt_km) * sin_lat,
+    ]
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LookAngles {
+    pub azimuth: f64,
+    pub elevation: f64,
+    pub range_km: f64,
+}
+
+/// Azimuth/elevation (degrees) of an Earth-fixed target seen from a geodetic observer.
+pub fn look_angles(lat_deg: f64, lon_deg: f64, observer: [f64; 3], target: [f64; 3]) -> LookAngles {
+    let rho = [target[0] - observer[0], target[1] - observer[1], target[2] - observer[2]];
+    let (sin_lat, cos_lat) = lat_deg.to_radians().sin_cos();
+    let (sin_lon, cos_lon) = lon_deg.to_radians().sin_cos();
+    let east = -sin_lon * rho[0] + cos_lon * rho[1];
+    let north = -sin_lat * cos_lon * rho[0] - sin_lat * sin_lon * rho[1] + cos_lat * rho[2];
+    let up = cos_lat * cos_lon * rho[0] + cos_lat * sin_lon * rho[1] + sin_lat * rho[2];
+    LookAngles {
+        azimuth: east.atan2(north).to_degrees().rem_euclid(360.0),
+        elevation: up.atan2((east * east + north * north).sqrt()).to_degrees(),
+        range_km: norm(rho),
+    }
+}
+
+/// Low-precision solar position (Astronomical Almanac, ~0.01 deg) in the TEME/ECI frame, km.
+pub fn sun_position_teme(at: DateTime<Utc>) -> [f64; 3] {
+    const AU_KM: f64 = 149_597_870.7;
+    let n = julian_date(at) - 2_451_545.0;
+    let mean_lon = 280.460 + 0.985_647_4 * n;
+    let g = (357.528 + 0.985_600_3 * n).to_radians();
+    let ecl_lon = (mean_lon + 1.915 * g.sin() + 0.020 * (2.0 * g).sin()).to_radians();
+    let obliquity = (23.439 - 0.000_000_4 * n).to_radians();
+    let dist = (1.000_14 - 0.016_71 * g.cos() - 0.000_14 * (2.0 * g).cos()) * AU_KM;
+    [
+        dist * ecl_lon.cos(),
+        dist * obliquity.cos() * ecl_lon.sin(),
+        dist * obliquity.sin() * ecl_lon.sin(),
+    ]
+}
+
+pub fn normalize_lon(lon: f64) -> f64 {
+    let wrapped = (lon + 180.0).rem_euclid(360.0) - 180.0;
+    if wrapped == -180.0 && lon > 0.0 {
+        180.0
+    } else {
+        wrapped
+    }
+}
+
+pub fn norm(v: [f64; 3]) -> f64 {
+    (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt()
+}
+
+fn field(line: &str, start: usize, end: usize) -> Result<&str> {
+    line.get(start..end.min(line.len()))
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| anyhow!("TLE line too short: {line:?}"))
+}
+
+fn parse_f64(line: &str, start: usize, end: usize, what: &str) -> Result<f64> {
+    field(line, start, end)?
+        .trim()
+        .parse()
+        .with_context(|| format!("invalid TLE {what}"))
+}
+
+/// Parses the packed "mantissa + exponent" notation used for B* ("-11606-4" = -0.11606e-4).
+fn parse_implied_exponent(raw: &str) -> Result<f64> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return Ok(0.0);
+    }
+    let (sign, rest) = match raw.as_bytes()[0] {
+        b'-' => (-1.0, &raw[1..]),
+        b'+' => (1.0, &raw[1..]),
+        _ => (1.0, raw),
+    };
+    let split = rest.rfind(['-', '+']).filter(|&i| i > 0);
+    let (mantissa, exponent) = match split {
+        Some(i) => (&rest[..i], rest[i..].parse::<i32>()?),
+        None => (rest, 0),
+    };
+    let mantissa: f64 = format!("0.{}", mantissa.trim()).parse()?;
+    Ok(sign * mantissa * 10f64.powi(exponent))
+}
+
+/// Bundled element sets used as a local stand-in for the TLE endpoint.
+#[cfg(test)]
+pub mod fixtures {
+    pub const ISS_TLE: &str = "1 25544U 98067A   24001.50000000  .00016717  00000-0  30194-3 0  9993
+2 25544  51.6416 208.9163 0005481  38.4419 321.7001 15.49815364432342";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Verification case 00005 from Vallado's SGP4 test suite.
+    const VALLADO_00005: &str = "1 00005U 58002B   00179.78495062  .00000023  00000-0  28098-4 0  4753
+2 00005  34.2682 348.7242 1859667 331.7664  19.3264 10.82419157413667";
+
+    #[test]
+    fn sgp4_matches_reference_vectors() {
+        let sgp4 = Sgp4::from_tle(&Tle::parse(VALLADO_00005).unwrap()).unwrap();
+        let at_epoch = sgp4.propagate_minutes(0.0).unwrap();
+        let expected = [7022.46529266, -1400.08296755, 0.03995155];
+        for (got, want) in at_epoch.position.iter().zip(expected) {
+            assert!((got - want).abs() < 1e-3, "{got} vs {want}");
+        }
+        let later = sgp4.propagate_minutes(360.0).unwrap();
+        let expected = [-7154.03120202, -3783.17682504, -3536.19412294];
+        for (got, want) in later.position.iter().zip(expected) {
+            assert!((got - want).abs() < 1e-3, "{got} vs {want}");
+        }
+        let expected_v = [4.741887409, -4.151817765, -2.093935425];
+        for (got, want) in later.velocity.iter().zip(expected_v) {
+            assert!((got - want).abs() < 1e-6, "{got} vs {want}");
+        }
+    }
+
+    #[test]
+    fn look_angles_point_straight_up_for_zenith_target() {
+        let observer = geodetic_to_ecef(55.75, 37.62, 0.0);
+        let target = geodetic_to_ecef(55.75, 37.62, 400.0);
+        let look = look_angles(55.75, 37.62, observer, target);
+        assert!((look.elevation - 90.0).abs() < 1e-6);
+        assert!((look.range_km - 400.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn tle_parse_reads_name_and_norad_id() {
+        let tle = Tle::parse(&format!("ISS (ZARYA)\n{}\n", super::fixtures::ISS_TLE)).unwrap();
+        assert_eq!(tle.name.as_deref(), Some("ISS (ZARYA)"));
+        assert!(tle.line1.starts_with("1 25544U"));
+    }
+
+    #[test]
+    fn iss_geodetic_is_in_leo() {
+        let tle = Tle::parse(super::fixtures::ISS_TLE).unwrap();
+        let sgp4 = Sgp4::from_tle(&tle).unwrap();
+        let at = sgp4.epoch + Duration::hours(3);
+        let state = sgp4.propagate(at).unwrap();
+        let geo = state.geodetic(at);
+        assert!(geo.altitude_km > 380.0 && geo.altitude_km < 460.0, "{}", geo.altitude_km);
+        assert!(geo.latitude.abs() <= 52.0);
+        assert!((state.speed_kmh() - 27_600.0).abs() < 300.0);
+    }
+}
@@ -0,0 +1,490 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, NaiveDateTime, SecondsFormat, TimeZone, Utc};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::domain::{OsdrCursor, OsdrFieldMatch, OsdrMappedItem, OsdrRecord, OsdrSortKey};
+
+/// Mapping matching the payload shapes seen so far. Override with `OSDR_FIELD_MAP_PATH`.
+const BUNDLED_FIELD_MAP: &str = include_str!("field_map.json");
+
+/// Declarative extraction of the indexed columns from OSDR items. `default` applies to
+/// every source without an entry in `sources`, which is keyed by the full source URL or
+/// by its host.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FieldMap {
+    pub default: FieldProfile,
+    #[serde(default)]
+    pub sources: BTreeMap<String, FieldProfile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FieldProfile {
+    pub dataset_id: FieldRule,
+    pub title: FieldRule,
+    pub status: FieldRule,
+    pub updated_at: FieldRule,
+}
+
+/// JSON pointers tried in order; the first whose value survives the coercion wins.
+/// `formats` are extra chrono patterns for `datetime`, `default` is used when nothing
+/// matches, and a `required` field that stays empty makes the item skipped.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FieldRule {
+    pub paths: Vec<String>,
+    #[serde(default)]
+    pub coerce: Coercion,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub formats: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Coercion {
+    /// Non-empty strings (trimmed), numbers and booleans as text.
+    #[default]
+    String,
+    /// RFC 3339, any of the rule's `formats` (read as UTC), or integer epoch seconds.
+    Datetime,
+    EpochSeconds,
+    EpochMillis,
+}
+
+enum Coerced {
+    Text(String),
+    Time(DateTime<Utc>),
+}
+
+impl FieldMap {
+    pub fn load(path: Option<&str>) -> Result<Self> {
+        let raw = match path {
+            Some(path) => {
+                std::fs::read_to_string(path).with_context(|| format!("failed to read field map from {path}"))?
+            }
+            None => BUNDLED_FIELD_MAP.to_string(),
+        };
+        let value: Value = serde_json::from_str(&raw).context("field map is not valid JSON")?;
+        Self::from_value(value)
+    }
+
+    pub fn from_value(value: Value) -> Result<Self> {
+        let map: Self = serde_json::from_value(value).context("field map does not match the expected shape")?;
+        map.default.validate().context("default profile")?;
+        for (source, profile) in &map.sources {
+            profile.validate().with_context(|| format!("profile for {source}"))?;
+        }
+        Ok(map)
+    }
+
+    pub fn source_count(&self) -> usize {
+        self.sources.len()
+    }
+
+    /// Profile for a source with the key it was found under (`default` if none matched).
+    pub fn profile(&self, source: &str) -> (&str, &FieldProfile) {
+        let host = Url::parse(source).ok().and_then(|url| url.host_str().map(str::to_string));
+        [Some(source.to_string()), host]
+            .into_iter()
+            .flatten()
+            .find_map(|key| self.sources.get_key_value(&key))
+            .map(|(key, profile)| (key.as_str(), profile))
+            .unwrap_or(("default", &self.default))
+    }
+}
+
+impl FieldProfile {
+    pub fn from_value(value: Value) -> Result<Self> {
+        let profile: Self = serde_json::from_value(value).context("mapping does not match the expected shape")?;
+        profile.validate()?;
+        Ok(profile)
+    }
+
+    fn rules(&self) -> [(&'static str, &FieldRule); 4] {
+        [
+            ("dataset_id", &self.dataset_id),
+            ("title", &self.title),
+            ("status", &self.status),
+            ("updated_at", &self.updated_at),
+        ]
+    }
+
+    fn validate(&self) -> Result<()> {
+        // Items are keyed by dataset id, so one without it has nowhere to be stored.
+        if !self.dataset_id.required {
+            return Err(anyhow!("dataset_id: must be required"));
+        }
+        for (name, rule) in self.rules() {
+            if rule.paths.is_empty() && rule.default.is_none() {
+                return Err(anyhow!("{name}: needs at least one path or a default"));
+            }
+            if let Some(path) = rule.paths.iter().find(|path| !path.is_empty() && !path.starts_with('/')) {
+                return Err(anyhow!("{name}: `{path}` is not a JSON pointer (must start with `/`)"));
+            }
+        }
+        if self.updated_at.coerce == Coercion::String {
+            return Err(anyhow!("updated_at: coerce must be datetime, epoch_seconds or epoch_millis"));
+        }
+        if self.dataset_id.coerce != Coercion::String
+            || self.title.coerce != Coercion::String
+            || self.status.coerce != Coercion::String
+        {
+            return Err(anyhow!("dataset_id, title and status must use the string coercion"));
+        }
+        Ok(())
+    }
+
+    pub fn apply(&self, item: &Value) -> OsdrMappedItem {
+        let mut mapped = OsdrMappedItem::default();
+        for (name, rule) in self.rules() {
+            let (value, found) = rule.resolve(item);
+            if value.is_none() && rule.required && mapped.skipped.is_none() {
+                mapped.skipped = Some(format!("required field {name} not found"));
+            }
+            match (name, value) {
+                ("updated_at", Some(Coerced::Time(at))) => mapped.updated_at = Some(at),
+                ("dataset_id", Some(Coerced::Text(text))) => mapped.dataset_id = Some(text),
+                ("title", Some(Coerced::Text(text))) => mapped.title = Some(text),
+                ("status", Some(Coerced::Text(text))) => mapped.status = Some(text),
+                _ => {}
+            }
+            mapped.fields.insert(name.to_string(), found);
+        }
+        mapped
+    }
+}
+
+impl FieldRule {
+    fn resolve(&self, item: &Value) -> (Option<Coerced>, OsdrFieldMatch) {
+        for path in &self.paths {
+            let Some(raw) = item.pointer(path) else {
+                continue;
+            };
+            if let Some(value) = self.coerce(raw) {
+                let found = OsdrFieldMatch {
+                    path: Some(path.clone()),
+                    raw: Some(raw.clone()),
+                    defaulted: false,
+                };
+                return (Some(value), found);
+            }
+        }
+        let fallback = self.default.as_ref().and_then(|default| self.coerce(&Value::String(default.clone())));
+        let found = OsdrFieldMatch {
+            defaulted: fallback.is_some(),
+            ..OsdrFieldMatch::default()
+        };
+        (fallback, found)
+    }
+
+    fn coerce(&self, raw: &Value) -> Option<Coerced> {
+        let number = || raw.as_i64().or_else(|| raw.as_str()?.trim().parse().ok());
+        match self.coerce {
+            Coercion::String => match raw {
+                Value::String(text) => Some(text.trim()).filter(|text| !text.is_empty()).map(str::to_string),
+                Value::Number(_) | Value::Bool(_) => Some(raw.to_string()),
+                _ => None,
+            }
+            .map(Coerced::Text),
+            Coercion::Datetime => match raw {
+                Value::String(text) => parse_datetime(text.trim(), &self.formats),
+                _ => Utc.timestamp_opt(raw.as_i64()?, 0).single(),
+            }
+            .map(Coerced::Time),
+            Coercion::EpochSeconds => Utc.timestamp_opt(number()?, 0).single().map(Coerced::Time),
+            Coercion::EpochMillis => Utc.timestamp_millis_opt(number()?).single().map(Coerced::Time),
+        }
+    }
+}
+
+fn parse_datetime(text: &str, formats: &[String]) -> Option<DateTime<Utc>> {
+    if let Ok(at) = text.parse::<DateTime<Utc>>() {
+        return Some(at);
+    }
+    formats
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+        .map(|naive| Utc.from_utc_datetime(&naive))
+}
+
+/// Structural difference between two OSDR payloads as JSON Patch style operations
+/// (`add`, `remove`, `replace`). Objects are compared key by key; arrays and scalars are
+/// replaced whole. Removed and replaced entries keep the previous value under `old`.
+pub fn json_diff(old: &Value, new: &Value) -> Vec<Value> {
+    let mut ops = Vec::new();
+    diff_into(&mut ops, String::new(), old, new);
+    ops
+}
+
+fn diff_into(ops: &mut Vec<Value>, path: String, old: &Value, new: &Value) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => diff_objects(ops, &path, old, new),
+        _ if old == new => {}
+        _ => ops.push(json!({ "op": "replace", "path": path, "old": old, "value": new })),
+    }
+}
+
+fn diff_objects(ops: &mut Vec<Value>, path: &str, old: &Map<String, Value>, new: &Map<String, Value>) {
+    for (key, before) in old {
+        let child = format!("{path}/{}", escape_pointer(key));
+        match new.get(key) {
+            Some(after) => diff_into(ops, child, before, after),
+            None => ops.push(json!({ "op": "remove", "path": child, "old": before })),
+        }
+    }
+    for (key, after) in new {
+        if !old.contains_key(key) {
+            let child = format!("{path}/{}", escape_pointer(key));
+            ops.push(json!({ "op": "add", "path": child, "value": after }));
+        }
+    }
+}
+
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Opaque, URL-safe form of a list cursor.
+pub fn encode_cursor(cursor: &OsdrCursor) -> String {
+    URL_SAFE_NO_PAD.encode(serde_json::to_vec(cursor).unwrap_or_default())
+}
+
+pub fn decode_cursor(raw: &str) -> Option<OsdrCursor> {
+    let bytes = URL_SAFE_NO_PAD.decode(raw.trim()).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+/// Cursor pointing just past `record` in the given ordering. Timestamps keep full
+/// precision so the keyset comparison resumes exactly after the row.
+pub fn cursor_after(record: &OsdrRecord, sort: OsdrSortKey, descending: bool) -> OsdrCursor {
+    let key = match sort {
+        OsdrSortKey::InsertedAt => Some(record.inserted_at.to_rfc3339_opts(SecondsFormat::AutoSi, true)),
+        OsdrSortKey::UpdatedAt => record
+            .updated_at
+            .map(|at| at.to_rfc3339_opts(SecondsFormat::AutoSi, true)),
+        OsdrSortKey::DatasetId => record.dataset_id.clone(),
+        OsdrSortKey::Title => record.title.clone(),
+    };
+    OsdrCursor {
+        sort,
+        descending,
+        key,
+        id: record.id,
+    }
+}
+
+/// Markers `ts_headline` puts around matches. They are ASCII control characters, safe
+/// under any server encoding, and are stripped from the source text beforehand so only
+/// the highlighter can produce them.
+pub const SNIPPET_START: char = '\u{2}';
+pub const SNIPPET_STOP: char = '\u{3}';
+
+/// HTML-escapes a raw headline and only then turns the markers into `<mark>` tags, so
+/// upstream titles and descriptions cannot smuggle markup into the snippet.
+pub fn render_snippet(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len() + 16);
+    for ch in raw.chars() {
+        match ch {
+            SNIPPET_START => out.push_str("<mark>"),
+            SNIPPET_STOP => out.push_str("</mark>"),
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+/// The trimmed `q` of a search, rejecting empty and oversized queries.
+pub fn search_terms(q: Option<&str>) -> Result<&str> {
+    let q = q.map(str::trim).unwrap_or_default();
+    if q.is_empty() {
+        return Err(anyhow!("`q` is required"));
+    }
+    if q.chars().count() > 500 {
+        return Err(anyhow!("`q` is longer than 500 characters"));
+    }
+    Ok(q)
+}
+
+/// `(limit, offset)` of a search page, defaulting the limit and capping both.
+pub fn search_paging(limit: Option<i64>, offset: Option<i64>, default_limit: i64) -> (i64, i64) {
+    (limit.unwrap_or(default_limit).clamp(1, 200), offset.unwrap_or(0).clamp(0, 10_000))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Datelike;
+
+    #[test]
+    fn snippet_escapes_upstream_markup_around_marks() {
+        let raw = format!("<img src=x onerror=\"alert('x')\"> Mouse {SNIPPET_START}muscle{SNIPPET_STOP} & bone");
+        assert_eq!(
+            render_snippet(&raw),
+            "&lt;img src=x onerror=&quot;alert(&#39;x&#39;)&quot;&gt; Mouse <mark>muscle</mark> &amp; bone"
+        );
+    }
+
+    #[test]
+    fn search_terms_and_paging_are_bounded() {
+        assert_eq!(search_terms(Some("  mouse muscle ")).unwrap(), "mouse muscle");
+        assert!(search_terms(Some("   ")).is_err());
+        assert!(search_terms(None).is_err());
+        assert!(search_terms(Some(&"я".repeat(500))).is_ok());
+        assert!(search_terms(Some(&"a".repeat(501))).is_err());
+        assert_eq!(search_paging(None, None, 20), (20, 0));
+        assert_eq!(search_paging(Some(0), Some(-5), 20), (1, 0));
+        assert_eq!(search_paging(Some(1_000), Some(50_000), 20), (200, 10_000));
+    }
+
+    #[test]
+    fn diff_reports_nested_changes_as_pointer_ops() {
+        let old = json!({
+            "status": "draft",
+            "meta": { "organism": "mouse", "a/b": 1 },
+            "files": ["a.csv"],
+            "gone": true
+        });
+        let new = json!({
+            "status": "public",
+            "meta": { "organism": "mouse", "a/b": 2 },
+            "files": ["a.csv", "b.csv"],
+            "added": null
+        });
+        let ops = json_diff(&old, &new);
+        assert_eq!(
+            ops,
+            vec![
+                json!({ "op": "replace", "path": "/files", "old": ["a.csv"], "value": ["a.csv", "b.csv"] }),
+                json!({ "op": "remove", "path": "/gone", "old": true }),
+                json!({ "op": "replace", "path": "/meta/a~1b", "old": 1, "value": 2 }),
+                json!({ "op": "replace", "path": "/status", "old": "draft", "value": "public" }),
+                json!({ "op": "add", "path": "/added", "value": null }),
+            ]
+        );
+        assert!(json_diff(&new, &new).is_empty());
+        assert_eq!(json_diff(&json!({}), &json!({ "id": "OSD-1" })).len(), 1);
+    }
+
+    #[test]
+    fn bundled_field_map_resolves_fallbacks_and_skips_items_without_id() {
+        let map = FieldMap::load(None).unwrap();
+        let (name, profile) = map.profile("https://visualization.osdr.nasa.gov/biodata/api/v2/datasets/");
+        assert_eq!(name, "default");
+
+        let mapped = profile.apply(&json!({
+            "accession": "OSD-7",
+            "name": "  Mouse liver  ",
+            "state": 3,
+            "timestamp": 1_700_000_000
+        }));
+        assert_eq!(mapped.dataset_id.as_deref(), Some("OSD-7"));
+        assert_eq!(mapped.title.as_deref(), Some("Mouse liver"));
+        assert_eq!(mapped.status.as_deref(), Some("3"));
+        assert_eq!(mapped.updated_at.map(|at| at.timestamp()), Some(1_700_000_000));
+        assert_eq!(mapped.fields["dataset_id"].path.as_deref(), Some("/accession"));
+        assert!(mapped.skipped.is_none());
+
+        let iso = profile.apply(&json!({ "id": "OSD-8", "updated": "2025-01-01T00:00:00Z" }));
+        assert_eq!(iso.updated_at.map(|at| at.year()), Some(2025));
+        let naive = profile.apply(&json!({ "id": "OSD-9", "updated": "2025-02-03 04:05:06" }));
+        assert_eq!(naive.updated_at.map(|at| at.month()), Some(2));
+        assert!(profile.apply(&json!({ "title": "orphan", "id": "" })).skipped.is_some());
+    }
+
+    #[test]
+    fn per_source_profiles_use_pointers_coercions_and_defaults() {
+        let mut profile = serde_json::to_value(&FieldMap::load(None).unwrap().default).unwrap();
+        profile["dataset_id"] = json!({ "paths": ["/study/accession"], "required": true });
+        profile["status"] = json!({ "paths": ["/meta/release/status"], "default": "unknown" });
+        profile["updated_at"] = json!({ "paths": ["/meta/modified_ms"], "coerce": "epoch_millis" });
+        let map = FieldMap::from_value(json!({
+            "default": FieldMap::load(None).unwrap().default,
+            "sources": { "osdr.example": profile }
+        }))
+        .unwrap();
+        assert_eq!(map.source_count(), 1);
+
+        let (name, profile) = map.profile("https://osdr.example/api/v3/studies?page=1");
+        assert_eq!(name, "osdr.example");
+        let mapped = profile.apply(&json!({
+            "study": { "accession": "OSD-42", "title": "ignored" },
+            "meta": { "modified_ms": "1700000000123" }
+        }));
+        assert_eq!(mapped.dataset_id.as_deref(), Some("OSD-42"));
+        assert_eq!(mapped.status.as_deref(), Some("unknown"));
+        assert!(mapped.fields["status"].defaulted);
+        assert_eq!(mapped.updated_at.map(|at| at.timestamp_millis()), Some(1_700_000_000_123));
+
+        let bad = json!({ "dataset_id": { "paths": ["id"] }, "title": { "paths": [] }, "status": { "paths": [] },
+                          "updated_at": { "paths": [], "coerce": "datetime" } });
+        assert!(FieldProfile::from_value(bad).is_err());
+
+        let mut optional_id = serde_json::to_value(&FieldMap::load(None).unwrap().default).unwrap();
+        optional_id["dataset_id"]["required"] = json!(false);
+        let err = FieldProfile::from_value(optional_id).unwrap_err();
+        assert!(err.to_string().contains("dataset_id"));
+    }
+
+    #[test]
+    fn changed_profile_remaps_an_unchanged_payload() {
+        let item = json!({
+            "id": "OSD-5",
+            "title": "Old title",
+            "name": "Rodent Research 5",
+            "status": "draft",
+            "release": { "state": "public" },
+            "updated": "2024-01-01T00:00:00Z",
+            "modified_ms": 1_717_200_000_000_i64
+        });
+        let before = FieldMap::load(None).unwrap().default.apply(&item);
+
+        let mut changed = serde_json::to_value(&FieldMap::load(None).unwrap().default).unwrap();
+        changed["title"] = json!({ "paths": ["/name"] });
+        changed["status"] = json!({ "paths": ["/release/state"] });
+        changed["updated_at"] = json!({ "paths": ["/modified_ms"], "coerce": "epoch_millis" });
+        let after = FieldProfile::from_value(changed).unwrap().apply(&item);
+
+        // Same raw payload, so only the indexed columns can tell the upsert to rewrite the row.
+        assert_eq!(before.dataset_id, after.dataset_id);
+        assert_eq!((before.title.as_deref(), after.title.as_deref()), (Some("Old title"), Some("Rodent Research 5")));
+        assert_eq!((before.status.as_deref(), after.status.as_deref()), (Some("draft"), Some("public")));
+        assert_ne!(before.updated_at, after.updated_at);
+    }
+
+    #[test]
+    fn cursor_round_trips_and_keeps_timestamp_precision() {
+        let record = OsdrRecord {
+            id: 42,
+            dataset_id: Some("OSD-42".to_string()),
+            title: None,
+            status: None,
+            updated_at: None,
+            inserted_at: Utc.timestamp_opt(1_700_000_000, 123_456_000).unwrap(),
+            last_seen_at: Utc.timestamp_opt(1_700_000_000, 0).unwrap(),
+            removed_at: None,
+            raw: json!({}),
+        };
+        let cursor = cursor_after(&record, OsdrSortKey::InsertedAt, true);
+        assert_eq!(cursor.key.as_deref(), Some("2023-11-14T22:13:20.123456Z"));
+        let encoded = encode_cursor(&cursor);
+        assert!(encoded.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_'));
+        assert_eq!(decode_cursor(&encoded), Some(cursor));
+        assert_eq!(cursor_after(&record, OsdrSortKey::Title, false).key, None);
+        assert_eq!(decode_cursor("not a cursor"), None);
+    }
+}
@@ -4,12 +4,20 @@ use chrono::{DateTime, Utc};
 use serde_json::{json, Value};
 use sqlx::{PgPool, Row};
 
-use crate::domain::{IssEntry, OsdrRecord, SpaceCacheItem};
+use crate::domain::{
+    AltitudeBucket, Geofence, GeofenceEvent, GeofenceShape, GeofenceTransition, IssAnomaly, IssEntry, IssPosition,
+    IssRollup, IssTrackNeighbours, IssTrackPoint, OrbitEvent, OrbitEventKind, OsdrCursor, OsdrFile, OsdrListFilter,
+    OsdrRecord, OsdrRemovedFilter, OsdrRevision, OsdrRevisionKind, OsdrSearchHit, OsdrSortKey, OsdrSyncReport, RollupPeriod,
+    SpaceCacheItem, UpsertOutcome,
+};
+use crate::osdr::{json_diff, render_snippet, SNIPPET_START, SNIPPET_STOP};
 
 #[derive(Clone)]
 pub struct Repositories {
-    pool: PgPool,
     pub iss: IssRepo,
+    pub rollup: RollupRepo,
+    pub geofence: GeofenceRepo,
+    pub orbit: OrbitRepo,
     pub osdr: OsdrRepo,
     pub cache: CacheRepo,
 }
@@ -19,6 +27,21 @@ pub struct IssRepo {
     pool: PgPool,
 }
 
+#[derive(Clone)]
+pub struct RollupRepo {
+    pool: PgPool,
+}
+
+#[derive(Clone)]
+pub struct GeofenceRepo {
+    pool: PgPool,
+}
+
+#[derive(Clone)]
+pub struct OrbitRepo {
+    pool: PgPool,
+}
+
 #[derive(Clone)]
 pub struct OsdrRepo {
     pool: PgPool,
@@ -33,18 +56,19 @@ impl Repositories {
     pub fn new(pool: PgPool) -> Self {
         Self {
             iss: IssRepo { pool: pool.clone() },
+            rollup: RollupRepo { pool: pool.clone() },
+            geofence: GeofenceRepo { pool: pool.clone() },
+            orbit: OrbitRepo { pool: pool.clone() },
             osdr: OsdrRepo { pool: pool.clone() },
-            cache: CacheRepo { pool: pool.clone() },
-            pool,
+            cache: CacheRepo { pool },
         }
     }
 
-    pub fn pool(&self) -> PgPool {
-        self.pool.clone()
-    }
-
     pub async fn migrate(&self) -> anyhow::Result<()> {
         self.iss.init().await?;
+        self.rollup.init().await?;
+        self.geofence.init().await?;
+        self.orbit.init().await?;
         self.osdr.init().await?;
         self.cache.init().await?;
         Ok(())
@@ -63,59 +87,795 @@ impl IssRepo {
         )
         .execute(&self.pool)
         .await?;
+        // The typed columns arrive together, so a missing `latitude` means the table
+        // predates them and its rows need the one-off backfill below; the same goes for
+        // `content_hash`, which came later.
+        let mut tx = self.pool.begin().await?;
+        let typed = Self::has_column(&mut tx, "latitude").await?;
+        let hashed = Self::has_column(&mut tx, "content_hash").await?;
+        sqlx::query(
+            "ALTER TABLE iss_fetch_log
+                ADD COLUMN IF NOT EXISTS latitude DOUBLE PRECISION,
+                ADD COLUMN IF NOT EXISTS longitude DOUBLE PRECISION,
+                ADD COLUMN IF NOT EXISTS altitude DOUBLE PRECISION,
+                ADD COLUMN IF NOT EXISTS velocity DOUBLE PRECISION,
+                ADD COLUMN IF NOT EXISTS visibility TEXT,
+                ADD COLUMN IF NOT EXISTS footprint DOUBLE PRECISION,
+                ADD COLUMN IF NOT EXISTS observed_at TIMESTAMPTZ,
+                ADD COLUMN IF NOT EXISTS norad_id INTEGER NOT NULL DEFAULT 25544,
+                ADD COLUMN IF NOT EXISTS anomalies TEXT[] NOT NULL DEFAULT '{}',
+                ADD COLUMN IF NOT EXISTS content_hash TEXT",
+        )
+        .execute(&mut *tx)
+        .await?;
+        if !typed {
+            let backfilled = Self::backfill_positions(&mut tx).await?;
+            tracing::info!("backfilled typed position columns for {backfilled} iss rows");
+        }
+        if !hashed {
+            sqlx::query("UPDATE iss_fetch_log SET content_hash = md5(payload::text) WHERE content_hash IS NULL")
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_iss_fetch_log_norad_time
+             ON iss_fetch_log(norad_id, fetched_at DESC)",
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_iss_fetch_log_anomalies
+             ON iss_fetch_log(norad_id, id DESC) WHERE cardinality(anomalies) > 0",
+        )
+        .execute(&self.pool)
+        .await?;
+        // Bounding-box prefilter for overflight scans over the clean history.
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_iss_fetch_log_norad_position
+             ON iss_fetch_log(norad_id, latitude, longitude) WHERE cardinality(anomalies) = 0",
+        )
+        .execute(&self.pool)
+        .await?;
         Ok(())
     }
 
-    pub async fn insert_entry(&self, source_url: &str, payload: Value) -> sqlx::Result<IssEntry> {
+    async fn has_column(conn: &mut sqlx::PgConnection, column: &str) -> sqlx::Result<bool> {
+        sqlx::query_scalar(
+            "SELECT EXISTS(
+                SELECT 1 FROM information_schema.columns
+                WHERE table_schema = current_schema()
+                  AND table_name = 'iss_fetch_log' AND column_name = $1
+            )",
+        )
+        .bind(column)
+        .fetch_one(conn)
+        .await
+    }
+
+    /// Fills the typed position columns for rows written before they existed.
+    async fn backfill_positions(conn: &mut sqlx::PgConnection) -> sqlx::Result<u64> {
+        let result = sqlx::query(
+            "UPDATE iss_fetch_log
+             SET latitude = (payload->>'latitude')::float8,
+                 longitude = (payload->>'longitude')::float8,
+                 altitude = CASE WHEN jsonb_typeof(payload->'altitude') = 'number'
+                                 THEN (payload->>'altitude')::float8 END,
+                 velocity = CASE WHEN jsonb_typeof(payload->'velocity') = 'number'
+                                 THEN (payload->>'velocity')::float8 END,
+                 visibility = CASE WHEN jsonb_typeof(payload->'visibility') = 'string'
+                                   THEN payload->>'visibility' END,
+                 footprint = CASE WHEN jsonb_typeof(payload->'footprint') = 'number'
+                                  THEN (payload->>'footprint')::float8 END,
+                 observed_at = CASE WHEN jsonb_typeof(payload->'timestamp') = 'number'
+                                    THEN to_timestamp((payload->>'timestamp')::float8) END
+             WHERE latitude IS NULL
+               AND jsonb_typeof(payload->'latitude') = 'number'
+               AND jsonb_typeof(payload->'longitude') = 'number'",
+        )
+        .execute(conn)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn insert_entry(
+        &self,
+        norad_id: i32,
+        source_url: &str,
+        position: Option<&IssPosition>,
+        anomalies: &[IssAnomaly],
+        payload: Value,
+    ) -> sqlx::Result<IssEntry> {
+        let anomalies: Vec<&str> = anomalies.iter().map(IssAnomaly::as_str).collect();
         sqlx::query(
-            "INSERT INTO iss_fetch_log(source_url, payload)
-             VALUES ($1,$2)
-             RETURNING id, fetched_at, source_url, payload",
+            "INSERT INTO iss_fetch_log(source_url, payload, latitude, longitude, altitude, velocity,
+                                       visibility, footprint, observed_at, norad_id, anomalies, content_hash)
+             VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,md5($2::text))
+             RETURNING id, norad_id, fetched_at, source_url, payload, latitude, longitude, altitude,
+                       velocity, visibility, footprint, observed_at, anomalies",
         )
         .bind(source_url)
         .bind(payload)
-        .map(|row: sqlx::postgres::PgRow| IssEntry {
-            id: row.get("id"),
-            fetched_at: row.get("fetched_at"),
-            source_url: row.get("source_url"),
-            payload: row.get("payload"),
-        })
+        .bind(position.map(|p| p.latitude))
+        .bind(position.map(|p| p.longitude))
+        .bind(position.and_then(|p| p.altitude))
+        .bind(position.and_then(|p| p.velocity))
+        .bind(position.and_then(|p| p.visibility.clone()))
+        .bind(position.and_then(|p| p.footprint))
+        .bind(position.and_then(|p| p.observed_at))
+        .bind(norad_id)
+        .bind(&anomalies)
+        .map(map_iss_entry)
         .fetch_one(&self.pool)
         .await
     }
 
-    pub async fn last_entry(&self) -> sqlx::Result<Option<IssEntry>> {
+    /// Returns the newest row for the satellite if the given snapshot repeats it, either by
+    /// upstream timestamp or by payload content (jsonb text is canonical, so md5 is stable).
+    pub async fn find_duplicate_of_last(
+        &self,
+        norad_id: i32,
+        observed_at: Option<DateTime<Utc>>,
+        payload: &Value,
+    ) -> sqlx::Result<Option<IssEntry>> {
+        sqlx::query(
+            "SELECT * FROM (
+                 SELECT id, norad_id, fetched_at, source_url, payload, latitude, longitude, altitude,
+                        velocity, visibility, footprint, observed_at, anomalies, content_hash
+                 FROM iss_fetch_log
+                 WHERE norad_id = $1
+                 ORDER BY id DESC LIMIT 1
+             ) last
+             WHERE last.content_hash = md5($2::jsonb::text)
+                OR (last.observed_at IS NOT NULL AND last.observed_at = $3)",
+        )
+        .bind(norad_id)
+        .bind(payload)
+        .bind(observed_at)
+        .map(map_iss_entry)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    pub async fn last_entry(&self, norad_id: i32) -> sqlx::Result<Option<IssEntry>> {
         sqlx::query(
-            "SELECT id, fetched_at, source_url, payload
+            "SELECT id, norad_id, fetched_at, source_url, payload, latitude, longitude, altitude,
+                    velocity, visibility, footprint, observed_at, anomalies
              FROM iss_fetch_log
+             WHERE norad_id = $1
              ORDER BY id DESC LIMIT 1",
         )
-        .map(|row: sqlx::postgres::PgRow| IssEntry {
-            id: row.get("id"),
-            fetched_at: row.get("fetched_at"),
-            source_url: row.get("source_url"),
-            payload: row.get("payload"),
-        })
+        .bind(norad_id)
+        .map(map_iss_entry)
         .fetch_optional(&self.pool)
         .await
     }
 
-    pub async fn last_points(&self, limit: i64) -> sqlx::Result<Vec<IssEntry>> {
+    pub async fn last_points(&self, norad_id: i32, limit: i64, clean_only: bool) -> sqlx::Result<Vec<IssEntry>> {
         sqlx::query(
-            "SELECT id, fetched_at, source_url, payload
+            "SELECT id, norad_id, fetched_at, source_url, payload, latitude, longitude, altitude,
+                    velocity, visibility, footprint, observed_at, anomalies
              FROM iss_fetch_log
-             ORDER BY id DESC LIMIT $1",
+             WHERE norad_id = $1 AND (NOT $3 OR cardinality(anomalies) = 0)
+             ORDER BY id DESC LIMIT $2",
         )
+        .bind(norad_id)
         .bind(limit)
-        .map(|row: sqlx::postgres::PgRow| IssEntry {
+        .bind(clean_only)
+        .map(map_iss_entry)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Clean samples at or before `at` and strictly after it, nearest on each side.
+    pub async fn bracketing(
+        &self,
+        norad_id: i32,
+        at: DateTime<Utc>,
+    ) -> sqlx::Result<(Option<IssTrackPoint>, Option<IssTrackPoint>)> {
+        let side = |sql: &'static str| {
+            sqlx::query(sql)
+                .bind(norad_id)
+                .bind(at)
+                .map(map_track_point)
+                .fetch_optional(&self.pool)
+        };
+        let before = side(
+            "SELECT fetched_at, latitude, longitude, altitude, velocity
+             FROM iss_fetch_log
+             WHERE norad_id = $1 AND fetched_at <= $2
+               AND latitude IS NOT NULL AND longitude IS NOT NULL AND cardinality(anomalies) = 0
+             ORDER BY fetched_at DESC LIMIT 1",
+        )
+        .await?;
+        let after = side(
+            "SELECT fetched_at, latitude, longitude, altitude, velocity
+             FROM iss_fetch_log
+             WHERE norad_id = $1 AND fetched_at > $2
+               AND latitude IS NOT NULL AND longitude IS NOT NULL AND cardinality(anomalies) = 0
+             ORDER BY fetched_at ASC LIMIT 1",
+        )
+        .await?;
+        Ok((before, after))
+    }
+
+    /// Clean samples inside the lat/lon box, each with its clean neighbours from the full
+    /// history. `lon_ranges` holds two ranges so boxes wrapping the antimeridian can be
+    /// split; an unused range is passed empty (min > max).
+    pub async fn within_box(
+        &self,
+        norad_id: i32,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        lat_range: (f64, f64),
+        lon_ranges: [(f64, f64); 2],
+    ) -> sqlx::Result<Vec<IssTrackNeighbours>> {
+        sqlx::query(
+            "WITH hits AS (
+                 SELECT fetched_at, latitude, longitude, altitude, velocity
+                 FROM iss_fetch_log
+                 WHERE norad_id = $1 AND fetched_at >= $2 AND fetched_at <= $3
+                   AND cardinality(anomalies) = 0
+                   AND latitude BETWEEN $4 AND $5
+                   AND (longitude BETWEEN $6 AND $7 OR longitude BETWEEN $8 AND $9)
+             )
+             SELECT h.fetched_at, h.latitude, h.longitude, h.altitude, h.velocity,
+                    p.fetched_at AS prev_fetched_at, p.latitude AS prev_latitude, p.longitude AS prev_longitude,
+                    p.altitude AS prev_altitude, p.velocity AS prev_velocity,
+                    n.fetched_at AS next_fetched_at, n.latitude AS next_latitude, n.longitude AS next_longitude,
+                    n.altitude AS next_altitude, n.velocity AS next_velocity
+             FROM hits h
+             LEFT JOIN LATERAL (
+                 SELECT fetched_at, latitude, longitude, altitude, velocity
+                 FROM iss_fetch_log
+                 WHERE norad_id = $1 AND fetched_at < h.fetched_at
+                   AND latitude IS NOT NULL AND longitude IS NOT NULL AND cardinality(anomalies) = 0
+                 ORDER BY fetched_at DESC LIMIT 1
+             ) p ON true
+             LEFT JOIN LATERAL (
+                 SELECT fetched_at, latitude, longitude, altitude, velocity
+                 FROM iss_fetch_log
+                 WHERE norad_id = $1 AND fetched_at > h.fetched_at
+                   AND latitude IS NOT NULL AND longitude IS NOT NULL AND cardinality(anomalies) = 0
+                 ORDER BY fetched_at ASC LIMIT 1
+             ) n ON true
+             ORDER BY h.fetched_at ASC",
+        )
+        .bind(norad_id)
+        .bind(from)
+        .bind(to)
+        .bind(lat_range.0)
+        .bind(lat_range.1)
+        .bind(lon_ranges[0].0)
+        .bind(lon_ranges[0].1)
+        .bind(lon_ranges[1].0)
+        .bind(lon_ranges[1].1)
+        .map(|row: sqlx::postgres::PgRow| {
+            let neighbour = |prefix: &str| {
+                let at: Option<DateTime<Utc>> = row.get(format!("{prefix}fetched_at").as_str());
+                at.map(|at| IssTrackPoint {
+                    at,
+                    lat: row.get(format!("{prefix}latitude").as_str()),
+                    lon: row.get(format!("{prefix}longitude").as_str()),
+                    altitude: row.get(format!("{prefix}altitude").as_str()),
+                    velocity: row.get(format!("{prefix}velocity").as_str()),
+                    propagated: false,
+                })
+            };
+            IssTrackNeighbours {
+                prev: neighbour("prev_"),
+                next: neighbour("next_"),
+                point: map_track_point(row),
+            }
+        })
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    pub async fn track(
+        &self,
+        norad_id: i32,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        clean_only: bool,
+    ) -> sqlx::Result<Vec<IssTrackPoint>> {
+        sqlx::query(
+            "SELECT fetched_at, latitude, longitude, altitude, velocity
+             FROM iss_fetch_log
+             WHERE norad_id = $1 AND fetched_at >= $2 AND fetched_at <= $3
+               AND latitude IS NOT NULL AND longitude IS NOT NULL
+               AND (NOT $4 OR cardinality(anomalies) = 0)
+             ORDER BY fetched_at ASC",
+        )
+        .bind(norad_id)
+        .bind(from)
+        .bind(to)
+        .bind(clean_only)
+        .map(map_track_point)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    pub async fn anomalies(&self, norad_id: i32, limit: i64) -> sqlx::Result<Vec<IssEntry>> {
+        sqlx::query(
+            "SELECT id, norad_id, fetched_at, source_url, payload, latitude, longitude, altitude,
+                    velocity, visibility, footprint, observed_at, anomalies
+             FROM iss_fetch_log
+             WHERE norad_id = $1 AND cardinality(anomalies) > 0
+             ORDER BY id DESC LIMIT $2",
+        )
+        .bind(norad_id)
+        .bind(limit)
+        .map(map_iss_entry)
+        .fetch_all(&self.pool)
+        .await
+    }
+}
+
+fn map_track_point(row: sqlx::postgres::PgRow) -> IssTrackPoint {
+    IssTrackPoint {
+        at: row.get("fetched_at"),
+        lat: row.get("latitude"),
+        lon: row.get("longitude"),
+        altitude: row.get("altitude"),
+        velocity: row.get("velocity"),
+        propagated: false,
+    }
+}
+
+fn map_iss_entry(row: sqlx::postgres::PgRow) -> IssEntry {
+    let latitude: Option<f64> = row.get("latitude");
+    let longitude: Option<f64> = row.get("longitude");
+    let position = latitude.zip(longitude).map(|(latitude, longitude)| IssPosition {
+        latitude,
+        longitude,
+        altitude: row.get("altitude"),
+        velocity: row.get("velocity"),
+        visibility: row.get("visibility"),
+        footprint: row.get("footprint"),
+        observed_at: row.get("observed_at"),
+    });
+    IssEntry {
+        id: row.get("id"),
+        norad_id: row.get("norad_id"),
+        fetched_at: row.get("fetched_at"),
+        source_url: row.get("source_url"),
+        payload: row.get("payload"),
+        position,
+        anomalies: row
+            .get::<Vec<String>, _>("anomalies")
+            .iter()
+            .filter_map(|raw| IssAnomaly::parse(raw))
+            .collect(),
+    }
+}
+
+impl RollupRepo {
+    async fn init(&self) -> sqlx::Result<()> {
+        for period in [RollupPeriod::Hourly, RollupPeriod::Daily] {
+            let ddl = format!(
+                "CREATE TABLE IF NOT EXISTS {}(
+                    norad_id INTEGER NOT NULL,
+                    bucket TIMESTAMPTZ NOT NULL,
+                    sample_count BIGINT NOT NULL,
+                    avg_velocity DOUBLE PRECISION,
+                    min_altitude DOUBLE PRECISION,
+                    max_altitude DOUBLE PRECISION,
+                    orbit_count BIGINT NOT NULL,
+                    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+                    PRIMARY KEY (norad_id, bucket)
+                )",
+                period.table()
+            );
+            sqlx::query(&ddl).execute(&self.pool).await?;
+        }
+        Ok(())
+    }
+
+    /// Re-aggregates raw samples from the newest existing bucket onwards (inclusive), so
+    /// the still-open bucket is refreshed and pruned history is never recomputed.
+    /// Orbits are counted as ascending equator crossings between consecutive samples.
+    pub async fn rollup(&self, period: RollupPeriod, norad_id: i32) -> sqlx::Result<u64> {
+        let sql = format!(
+            "WITH start AS (
+                 SELECT coalesce(max(bucket), '-infinity'::timestamptz) AS at
+                 FROM {table} WHERE norad_id = $1
+             ), samples AS (
+                 SELECT fetched_at, velocity, altitude, latitude,
+                        lag(latitude) OVER (ORDER BY fetched_at) AS prev_lat
+                 FROM iss_fetch_log, start
+                 WHERE norad_id = $1
+                   AND latitude IS NOT NULL
+                   AND cardinality(anomalies) = 0
+                   AND fetched_at >= start.at - interval '1 {unit}'
+             )
+             INSERT INTO {table}(norad_id, bucket, sample_count, avg_velocity, min_altitude,
+                                 max_altitude, orbit_count, updated_at)
+             SELECT $1, date_trunc('{unit}', fetched_at, 'UTC') AS b, count(*), avg(velocity),
+                    min(altitude), max(altitude),
+                    count(*) FILTER (WHERE prev_lat < 0 AND latitude >= 0), now()
+             FROM samples, start
+             WHERE fetched_at >= start.at
+             GROUP BY b
+             ON CONFLICT (norad_id, bucket) DO UPDATE
+             SET sample_count = EXCLUDED.sample_count,
+                 avg_velocity = EXCLUDED.avg_velocity,
+                 min_altitude = EXCLUDED.min_altitude,
+                 max_altitude = EXCLUDED.max_altitude,
+                 orbit_count = EXCLUDED.orbit_count,
+                 updated_at = EXCLUDED.updated_at",
+            table = period.table(),
+            unit = period.unit(),
+        );
+        let result = sqlx::query(&sql).bind(norad_id).execute(&self.pool).await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Deletes raw rows older than `cutoff` whose day has been rolled up after it closed.
+    pub async fn prune_raw(&self, cutoff: DateTime<Utc>) -> sqlx::Result<u64> {
+        let result = sqlx::query(
+            "DELETE FROM iss_fetch_log l
+             WHERE l.fetched_at < $1
+               AND EXISTS (
+                   SELECT 1 FROM iss_rollup_daily d
+                   WHERE d.norad_id = l.norad_id
+                     AND d.bucket = date_trunc('day', l.fetched_at, 'UTC')
+                     AND d.updated_at >= d.bucket + interval '1 day'
+               )",
+        )
+        .bind(cutoff)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn list(
+        &self,
+        period: RollupPeriod,
+        norad_id: i32,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> sqlx::Result<Vec<IssRollup>> {
+        let sql = format!(
+            "SELECT norad_id, bucket, sample_count, avg_velocity, min_altitude, max_altitude, orbit_count
+             FROM {}
+             WHERE norad_id = $1 AND bucket >= $2 AND bucket <= $3
+             ORDER BY bucket ASC",
+            period.table()
+        );
+        sqlx::query(&sql)
+            .bind(norad_id)
+            .bind(from)
+            .bind(to)
+            .map(|row: sqlx::postgres::PgRow| IssRollup {
+                norad_id: row.get("norad_id"),
+                bucket: row.get("bucket"),
+                sample_count: row.get("sample_count"),
+                avg_velocity: row.get("avg_velocity"),
+                min_altitude: row.get("min_altitude"),
+                max_altitude: row.get("max_altitude"),
+                orbit_count: row.get("orbit_count"),
+            })
+            .fetch_all(&self.pool)
+            .await
+    }
+}
+
+impl GeofenceRepo {
+    async fn init(&self) -> sqlx::Result<()> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS iss_geofences(
+                id BIGSERIAL PRIMARY KEY,
+                name TEXT NOT NULL,
+                shape JSONB NOT NULL,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS iss_geofence_events(
+                id BIGSERIAL PRIMARY KEY,
+                geofence_id BIGINT NOT NULL REFERENCES iss_geofences(id) ON DELETE CASCADE,
+                norad_id INTEGER NOT NULL,
+                kind TEXT NOT NULL,
+                entry_id BIGINT NOT NULL,
+                at TIMESTAMPTZ NOT NULL,
+                latitude DOUBLE PRECISION NOT NULL,
+                longitude DOUBLE PRECISION NOT NULL
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_iss_geofence_events_fence_at
+             ON iss_geofence_events(geofence_id, at DESC)",
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_iss_geofence_events_norad_fence
+             ON iss_geofence_events(norad_id, geofence_id, id DESC)",
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn create(&self, name: &str, shape: &GeofenceShape) -> sqlx::Result<Geofence> {
+        let row = sqlx::query(
+            "INSERT INTO iss_geofences(name, shape) VALUES ($1, $2)
+             RETURNING id, name, shape, created_at",
+        )
+        .bind(name)
+        .bind(json!(shape))
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(Geofence {
             id: row.get("id"),
-            fetched_at: row.get("fetched_at"),
-            source_url: row.get("source_url"),
-            payload: row.get("payload"),
+            name: row.get("name"),
+            shape: shape.clone(),
+            created_at: row.get("created_at"),
+        })
+    }
+
+    pub async fn get(&self, id: i64) -> sqlx::Result<Option<Geofence>> {
+        let row = sqlx::query("SELECT id, name, shape, created_at FROM iss_geofences WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.map(map_geofence).transpose()
+    }
+
+    pub async fn list(&self) -> sqlx::Result<Vec<Geofence>> {
+        let rows = sqlx::query("SELECT id, name, shape, created_at FROM iss_geofences ORDER BY id ASC")
+            .fetch_all(&self.pool)
+            .await?;
+        rows.into_iter().map(map_geofence).collect()
+    }
+
+    pub async fn delete(&self, id: i64) -> sqlx::Result<bool> {
+        let result = sqlx::query("DELETE FROM iss_geofences WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Latest transition per geofence for the satellite; absence means "outside".
+    pub async fn last_transitions(&self, norad_id: i32) -> sqlx::Result<HashMap<i64, GeofenceTransition>> {
+        let rows = sqlx::query(
+            "SELECT DISTINCT ON (geofence_id) geofence_id, kind
+             FROM iss_geofence_events
+             WHERE norad_id = $1
+             ORDER BY geofence_id, id DESC",
+        )
+        .bind(norad_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                let kind: String = row.get("kind");
+                GeofenceTransition::parse(&kind).map(|kind| (row.get("geofence_id"), kind))
+            })
+            .collect())
+    }
+
+    pub async fn insert_event(
+        &self,
+        geofence_id: i64,
+        kind: GeofenceTransition,
+        entry: &IssEntry,
+        position: &IssPosition,
+    ) -> sqlx::Result<()> {
+        sqlx::query(
+            "INSERT INTO iss_geofence_events(geofence_id, norad_id, kind, entry_id, at, latitude, longitude)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        )
+        .bind(geofence_id)
+        .bind(entry.norad_id)
+        .bind(kind.as_str())
+        .bind(entry.id)
+        .bind(entry.fetched_at)
+        .bind(position.latitude)
+        .bind(position.longitude)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn events(
+        &self,
+        geofence_id: i64,
+        norad_id: Option<i32>,
+        since: Option<DateTime<Utc>>,
+        limit: i64,
+    ) -> sqlx::Result<Vec<GeofenceEvent>> {
+        let rows = sqlx::query(
+            "SELECT id, geofence_id, norad_id, kind, entry_id, at, latitude, longitude
+             FROM iss_geofence_events
+             WHERE geofence_id = $1
+               AND ($2::int IS NULL OR norad_id = $2)
+               AND ($3::timestamptz IS NULL OR at > $3)
+             ORDER BY at DESC, id DESC
+             LIMIT $4",
+        )
+        .bind(geofence_id)
+        .bind(norad_id)
+        .bind(since)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                let kind: String = row.get("kind");
+                Some(GeofenceEvent {
+                    id: row.get("id"),
+                    geofence_id: row.get("geofence_id"),
+                    norad_id: row.get("norad_id"),
+                    kind: GeofenceTransition::parse(&kind)?,
+                    entry_id: row.get("entry_id"),
+                    at: row.get("at"),
+                    latitude: row.get("latitude"),
+                    longitude: row.get("longitude"),
+                })
+            })
+            .collect())
+    }
+}
+
+fn map_osdr_record(row: sqlx::postgres::PgRow) -> OsdrRecord {
+    OsdrRecord {
+        id: row.get("id"),
+        dataset_id: row.get("dataset_id"),
+        title: row.get("title"),
+        status: row.get("status"),
+        updated_at: row.get("updated_at"),
+        inserted_at: row.get("inserted_at"),
+        last_seen_at: row.get("last_seen_at"),
+        removed_at: row.get("removed_at"),
+        raw: row.get("raw"),
+    }
+}
+
+fn map_geofence(row: sqlx::postgres::PgRow) -> sqlx::Result<Geofence> {
+    let id: i64 = row.get("id");
+    let shape = serde_json::from_value(row.get("shape"))
+        .map_err(|err| sqlx::Error::Decode(format!("geofence {id} has an invalid shape: {err}").into()))?;
+    Ok(Geofence {
+        id,
+        name: row.get("name"),
+        shape,
+        created_at: row.get("created_at"),
+    })
+}
+
+impl OrbitRepo {
+    async fn init(&self) -> sqlx::Result<()> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS iss_orbit_events(
+                id BIGSERIAL PRIMARY KEY,
+                norad_id INTEGER NOT NULL,
+                kind TEXT NOT NULL,
+                at TIMESTAMPTZ NOT NULL,
+                altitude_before DOUBLE PRECISION NOT NULL,
+                altitude_after DOUBLE PRECISION NOT NULL,
+                delta_km DOUBLE PRECISION NOT NULL,
+                decay_km_per_day DOUBLE PRECISION,
+                detected_at TIMESTAMPTZ NOT NULL DEFAULT now()
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_iss_orbit_events_norad_at
+             ON iss_orbit_events(norad_id, at DESC)",
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Mean/min/max altitude of clean samples in fixed buckets aligned to the Unix epoch,
+    /// so repeated runs see the same bucket boundaries.
+    pub async fn altitude_buckets(
+        &self,
+        norad_id: i32,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        bucket_sec: i64,
+    ) -> sqlx::Result<Vec<AltitudeBucket>> {
+        sqlx::query(
+            "SELECT to_timestamp(floor(extract(epoch FROM fetched_at) / $4) * $4) AS bucket,
+                    count(*) AS samples,
+                    avg(altitude) AS mean_altitude,
+                    min(altitude) AS min_altitude,
+                    max(altitude) AS max_altitude
+             FROM iss_fetch_log
+             WHERE norad_id = $1 AND fetched_at >= $2 AND fetched_at < $3
+               AND altitude IS NOT NULL AND cardinality(anomalies) = 0
+             GROUP BY 1
+             ORDER BY 1",
+        )
+        .bind(norad_id)
+        .bind(from)
+        .bind(to)
+        .bind(bucket_sec as f64)
+        .map(|row: sqlx::postgres::PgRow| AltitudeBucket {
+            at: row.get("bucket"),
+            samples: row.get("samples"),
+            mean_altitude: row.get("mean_altitude"),
+            min_altitude: row.get("min_altitude"),
+            max_altitude: row.get("max_altitude"),
         })
         .fetch_all(&self.pool)
         .await
     }
+
+    /// Stores an event unless one of the same kind is already recorded within `dedupe`
+    /// of it; reruns over overlapping history may place the same burn a bucket apart.
+    pub async fn insert_event(&self, event: &OrbitEvent, dedupe: chrono::Duration) -> sqlx::Result<bool> {
+        let result = sqlx::query(
+            "INSERT INTO iss_orbit_events(norad_id, kind, at, altitude_before, altitude_after, delta_km, decay_km_per_day)
+             SELECT $1, $2, $3, $4, $5, $6, $7
+             WHERE NOT EXISTS (
+                 SELECT 1 FROM iss_orbit_events
+                 WHERE norad_id = $1 AND kind = $2 AND at BETWEEN $3 - $8 AND $3 + $8
+             )",
+        )
+        .bind(event.norad_id)
+        .bind(event.kind.as_str())
+        .bind(event.at)
+        .bind(event.altitude_before)
+        .bind(event.altitude_after)
+        .bind(event.delta_km)
+        .bind(event.decay_km_per_day)
+        .bind(dedupe)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn events(
+        &self,
+        norad_id: i32,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        limit: i64,
+    ) -> sqlx::Result<Vec<OrbitEvent>> {
+        let rows = sqlx::query(
+            "SELECT id, norad_id, kind, at, altitude_before, altitude_after, delta_km, decay_km_per_day, detected_at
+             FROM iss_orbit_events
+             WHERE norad_id = $1 AND at >= $2 AND at <= $3
+             ORDER BY at DESC
+             LIMIT $4",
+        )
+        .bind(norad_id)
+        .bind(from)
+        .bind(to)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                let kind: String = row.get("kind");
+                Some(OrbitEvent {
+                    id: row.get("id"),
+                    norad_id: row.get("norad_id"),
+                    kind: OrbitEventKind::parse(&kind)?,
+                    at: row.get("at"),
+                    altitude_before: row.get("altitude_before"),
+                    altitude_after: row.get("altitude_after"),
+                    delta_km: row.get("delta_km"),
+                    decay_km_per_day: row.get("decay_km_per_day"),
+                    detected_at: row.get("detected_at"),
+                })
+            })
+            .collect())
+    }
 }
 
 impl OsdrRepo {
@@ -140,48 +900,476 @@ impl OsdrRepo {
         )
         .execute(&self.pool)
         .await?;
+        sqlx::query("ALTER TABLE osdr_items ADD COLUMN IF NOT EXISTS raw_hash TEXT")
+            .execute(&self.pool)
+            .await?;
+        sqlx::query("UPDATE osdr_items SET raw_hash = md5(raw::text) WHERE raw_hash IS NULL")
+            .execute(&self.pool)
+            .await?;
+        // Title and id rank highest, then the description, then the structured study
+        // metadata (organism, assay, factors), whose string leaves are indexed wherever
+        // they sit in the payload.
+        sqlx::query(
+            "ALTER TABLE osdr_items ADD COLUMN IF NOT EXISTS search_tsv tsvector
+             GENERATED ALWAYS AS (
+                 setweight(to_tsvector('simple', coalesce(dataset_id, '')), 'A')
+                 || setweight(to_tsvector('english', coalesce(title, '')), 'A')
+                 || setweight(to_tsvector('english', coalesce(raw->>'description', '')), 'B')
+                 || setweight(coalesce(jsonb_to_tsvector('english', raw->'organism', '[\"string\"]'), ''), 'C')
+                 || setweight(coalesce(jsonb_to_tsvector('english', raw->'assay', '[\"string\"]'), ''), 'C')
+                 || setweight(coalesce(jsonb_to_tsvector('english', raw->'assays', '[\"string\"]'), ''), 'C')
+                 || setweight(coalesce(jsonb_to_tsvector('english', raw->'factors', '[\"string\"]'), ''), 'C')
+             ) STORED",
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS ix_osdr_items_search ON osdr_items USING GIN (search_tsv)")
+            .execute(&self.pool)
+            .await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS osdr_sync_runs(
+                id BIGSERIAL PRIMARY KEY,
+                source TEXT NOT NULL,
+                started_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+                finished_at TIMESTAMPTZ,
+                report JSONB
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS osdr_item_revisions(
+                id BIGSERIAL PRIMARY KEY,
+                item_id BIGINT NOT NULL REFERENCES osdr_items(id) ON DELETE CASCADE,
+                dataset_id TEXT NOT NULL,
+                sync_run_id BIGINT REFERENCES osdr_sync_runs(id) ON DELETE SET NULL,
+                kind TEXT NOT NULL,
+                recorded_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+                previous_updated_at TIMESTAMPTZ,
+                updated_at TIMESTAMPTZ,
+                previous_title TEXT,
+                title TEXT,
+                previous_status TEXT,
+                status TEXT,
+                diff JSONB NOT NULL
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS ix_osdr_item_revisions_dataset_recorded
+             ON osdr_item_revisions(dataset_id, recorded_at DESC)",
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query(
+            "ALTER TABLE osdr_items
+                 ADD COLUMN IF NOT EXISTS last_seen_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+                 ADD COLUMN IF NOT EXISTS removed_at TIMESTAMPTZ",
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query(
+            "ALTER TABLE osdr_items
+                 ADD COLUMN IF NOT EXISTS files_fetched_at TIMESTAMPTZ,
+                 ADD COLUMN IF NOT EXISTS files_source_url TEXT",
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS osdr_item_files(
+                id BIGSERIAL PRIMARY KEY,
+                item_id BIGINT NOT NULL REFERENCES osdr_items(id) ON DELETE CASCADE,
+                position INT NOT NULL,
+                file_name TEXT NOT NULL,
+                size_bytes BIGINT,
+                download_url TEXT,
+                category TEXT
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS ix_osdr_item_files_item
+             ON osdr_item_files(item_id, position)",
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS osdr_sync_state(
+                source TEXT PRIMARY KEY,
+                watermark TIMESTAMPTZ,
+                last_run_at TIMESTAMPTZ NOT NULL DEFAULT now()
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query("ALTER TABLE osdr_sync_state ADD COLUMN IF NOT EXISTS last_full_at TIMESTAMPTZ")
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Watermark and time of the last complete full sync for a source.
+    pub async fn sync_state(
+        &self,
+        source: &str,
+    ) -> sqlx::Result<(Option<DateTime<Utc>>, Option<DateTime<Utc>>)> {
+        let row = sqlx::query("SELECT watermark, last_full_at FROM osdr_sync_state WHERE source = $1")
+            .bind(source)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.map_or((None, None), |row| (row.get("watermark"), row.get("last_full_at"))))
+    }
+
+    pub async fn start_run(&self, source: &str) -> sqlx::Result<i64> {
+        sqlx::query("INSERT INTO osdr_sync_runs(source) VALUES ($1) RETURNING id")
+            .bind(source)
+            .map(|row: sqlx::postgres::PgRow| row.get::<i64, _>("id"))
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    pub async fn finish_run(&self, run_id: i64, report: &OsdrSyncReport) -> sqlx::Result<()> {
+        sqlx::query("UPDATE osdr_sync_runs SET finished_at = now(), report = $2 WHERE id = $1")
+            .bind(run_id)
+            .bind(json!(report))
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Records a completed run; the watermark only ever moves forward.
+    pub async fn save_watermark(
+        &self,
+        source: &str,
+        watermark: Option<DateTime<Utc>>,
+        full: bool,
+    ) -> sqlx::Result<()> {
+        sqlx::query(
+            "INSERT INTO osdr_sync_state(source, watermark, last_run_at, last_full_at)
+             VALUES ($1, $2, now(), CASE WHEN $3 THEN now() END)
+             ON CONFLICT (source) DO UPDATE
+             SET watermark = GREATEST(osdr_sync_state.watermark, EXCLUDED.watermark),
+                 last_run_at = now(),
+                 last_full_at = coalesce(EXCLUDED.last_full_at, osdr_sync_state.last_full_at)",
+        )
+        .bind(source)
+        .bind(watermark)
+        .bind(full)
+        .execute(&self.pool)
+        .await?;
         Ok(())
     }
 
+    /// Inserts or refreshes a dataset. Rows whose stored `raw` hashes the same are left
+    /// untouched, so unchanged datasets cost a lookup rather than a rewrite. Every insert
+    /// or change of an identified dataset is recorded in `osdr_item_revisions` within the
+    /// same transaction.
     pub async fn upsert_item(
         &self,
-        dataset_id: Option<String>,
+        sync_run_id: Option<i64>,
+        dataset_id: String,
         title: Option<String>,
         status: Option<String>,
         updated_at: Option<DateTime<Utc>>,
         raw: Value,
-    ) -> sqlx::Result<()> {
-        if let Some(ds) = dataset_id {
-            sqlx::query(
-                "INSERT INTO osdr_items(dataset_id, title, status, updated_at, raw)
-                 VALUES($1,$2,$3,$4,$5)
-                 ON CONFLICT (dataset_id) DO UPDATE
+    ) -> sqlx::Result<UpsertOutcome> {
+        let mut tx = self.pool.begin().await?;
+        let row = sqlx::query(
+            "WITH previous AS (
+                 SELECT title, status, updated_at, raw FROM osdr_items WHERE dataset_id = $1
+             ), upserted AS (
+                 INSERT INTO osdr_items(dataset_id, title, status, updated_at, raw, raw_hash)
+                 VALUES($1,$2,$3,$4,$5,md5($5::jsonb::text))
+                 ON CONFLICT (dataset_id) WHERE dataset_id IS NOT NULL DO UPDATE
                  SET title=EXCLUDED.title,
                      status=EXCLUDED.status,
                      updated_at=EXCLUDED.updated_at,
-                     raw=EXCLUDED.raw",
-            )
-            .bind(ds)
-            .bind(title)
-            .bind(status)
-            .bind(updated_at)
-            .bind(raw)
-            .execute(&self.pool)
-            .await?;
+                     raw=EXCLUDED.raw,
+                     raw_hash=EXCLUDED.raw_hash
+                 WHERE (osdr_items.title, osdr_items.status, osdr_items.updated_at, osdr_items.raw_hash)
+                       IS DISTINCT FROM (EXCLUDED.title, EXCLUDED.status, EXCLUDED.updated_at, EXCLUDED.raw_hash)
+                 RETURNING id, (xmax = 0) AS inserted
+             )
+             SELECT u.id, u.inserted, p.title AS previous_title, p.status AS previous_status,
+                    p.updated_at AS previous_updated_at, p.raw AS previous_raw
+             FROM upserted u
+             LEFT JOIN previous p ON true",
+        )
+        .bind(&dataset_id)
+        .bind(&title)
+        .bind(&status)
+        .bind(updated_at)
+        .bind(&raw)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(row) = row else {
+            return Ok(UpsertOutcome::Unchanged);
+        };
+        let (kind, outcome) = if row.get::<bool, _>("inserted") {
+            (OsdrRevisionKind::Created, UpsertOutcome::Inserted)
         } else {
-            sqlx::query(
-                "INSERT INTO osdr_items(dataset_id, title, status, updated_at, raw)
-                 VALUES($1,$2,$3,$4,$5)",
-            )
-            .bind::<Option<String>>(None)
-            .bind(title)
-            .bind(status)
-            .bind(updated_at)
-            .bind(raw)
-            .execute(&self.pool)
+            (OsdrRevisionKind::Updated, UpsertOutcome::Updated)
+        };
+        let previous_raw = row
+            .get::<Option<Value>, _>("previous_raw")
+            .unwrap_or_else(|| json!({}));
+        sqlx::query(
+            "INSERT INTO osdr_item_revisions(
+                 item_id, dataset_id, sync_run_id, kind, previous_updated_at, updated_at,
+                 previous_title, title, previous_status, status, diff
+             )
+             VALUES($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11)",
+        )
+        .bind(row.get::<i64, _>("id"))
+        .bind(&dataset_id)
+        .bind(sync_run_id)
+        .bind(kind.as_str())
+        .bind(row.get::<Option<DateTime<Utc>>, _>("previous_updated_at"))
+        .bind(updated_at)
+        .bind(row.get::<Option<String>, _>("previous_title"))
+        .bind(title)
+        .bind(row.get::<Option<String>, _>("previous_status"))
+        .bind(status)
+        .bind(Value::Array(json_diff(&previous_raw, &raw)))
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(outcome)
+    }
+
+    /// Stamps every dataset returned by a sync as seen, lifting any tombstone; returns how
+    /// many were restored (each gets a `restored` revision).
+    pub async fn mark_seen(&self, sync_run_id: i64, dataset_ids: &[String]) -> sqlx::Result<i64> {
+        sqlx::query(
+            "WITH seen AS (
+                 UPDATE osdr_items i
+                 SET last_seen_at = now(), removed_at = NULL
+                 FROM osdr_items old
+                 WHERE old.id = i.id AND i.dataset_id = ANY($1)
+                 RETURNING i.id, i.dataset_id, i.title, i.status, i.updated_at, old.removed_at AS was_removed
+             ), restored AS (
+                 INSERT INTO osdr_item_revisions(
+                     item_id, dataset_id, sync_run_id, kind, previous_updated_at, updated_at,
+                     previous_title, title, previous_status, status, diff
+                 )
+                 SELECT id, dataset_id, $2, 'restored', updated_at, updated_at, title, title, status, status, '[]'
+                 FROM seen
+                 WHERE was_removed IS NOT NULL
+                 RETURNING 1
+             )
+             SELECT count(*) AS c FROM restored",
+        )
+        .bind(dataset_ids)
+        .bind(sync_run_id)
+        .map(|row: sqlx::postgres::PgRow| row.get::<i64, _>("c"))
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    /// Marks identified datasets last seen before `cutoff` as removed, recording a
+    /// `removed` revision for each; returns how many were tombstoned.
+    pub async fn tombstone(&self, sync_run_id: i64, cutoff: DateTime<Utc>) -> sqlx::Result<i64> {
+        sqlx::query(
+            "WITH gone AS (
+                 UPDATE osdr_items
+                 SET removed_at = now()
+                 WHERE removed_at IS NULL AND dataset_id IS NOT NULL AND last_seen_at < $1
+                 RETURNING id, dataset_id, title, status, updated_at
+             ), removed AS (
+                 INSERT INTO osdr_item_revisions(
+                     item_id, dataset_id, sync_run_id, kind, previous_updated_at, updated_at,
+                     previous_title, title, previous_status, status, diff
+                 )
+                 SELECT id, dataset_id, $2, 'removed', updated_at, updated_at, title, title, status, status, '[]'
+                 FROM gone
+                 RETURNING 1
+             )
+             SELECT count(*) AS c FROM removed",
+        )
+        .bind(cutoff)
+        .bind(sync_run_id)
+        .map(|row: sqlx::postgres::PgRow| row.get::<i64, _>("c"))
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    pub async fn get(&self, dataset_id: &str) -> sqlx::Result<Option<OsdrRecord>> {
+        sqlx::query(
+            "SELECT id, dataset_id, title, status, updated_at, inserted_at, last_seen_at, removed_at, raw
+             FROM osdr_items
+             WHERE dataset_id = $1",
+        )
+        .bind(dataset_id)
+        .map(map_osdr_record)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Ranked full-text matches for a web-search style query (quoted phrases, `or`,
+    /// `-exclusions`), together with the total number of matches.
+    pub async fn search(
+        &self,
+        q: &str,
+        removed: OsdrRemovedFilter,
+        limit: i64,
+        offset: i64,
+    ) -> sqlx::Result<(i64, Vec<OsdrSearchHit>)> {
+        let total = sqlx::query(
+            "SELECT count(*) AS c FROM osdr_items
+             WHERE search_tsv @@ websearch_to_tsquery('english', $1)
+               AND ($2::text = 'include' OR ($2::text = 'only') = (removed_at IS NOT NULL))",
+        )
+        .bind(q)
+        .bind(removed.as_str())
+        .map(|row: sqlx::postgres::PgRow| row.get::<i64, _>("c"))
+        .fetch_one(&self.pool)
+        .await?;
+        // Matches are marked with placeholders and escaped into HTML by `render_snippet`.
+        let headline =
+            format!("StartSel={SNIPPET_START}, StopSel={SNIPPET_STOP}, MaxFragments=2, MaxWords=30, MinWords=8");
+        let hits = sqlx::query(
+            "SELECT i.id, i.dataset_id, i.title, i.status, i.updated_at, i.inserted_at, i.last_seen_at,
+                    i.removed_at, i.raw,
+                    ts_rank_cd(i.search_tsv, q.query, 32) AS rank,
+                    ts_headline(
+                        'english',
+                        translate(concat_ws(' — ', i.title, i.raw->>'description'), $6, ''),
+                        q.query,
+                        $5
+                    ) AS snippet
+             FROM osdr_items i, websearch_to_tsquery('english', $1) AS q(query)
+             WHERE i.search_tsv @@ q.query
+               AND ($4::text = 'include' OR ($4::text = 'only') = (i.removed_at IS NOT NULL))
+             ORDER BY rank DESC, i.updated_at DESC NULLS LAST, i.id DESC
+             LIMIT $2 OFFSET $3",
+        )
+        .bind(q)
+        .bind(limit)
+        .bind(offset)
+        .bind(removed.as_str())
+        .bind(headline)
+        .bind(format!("{SNIPPET_START}{SNIPPET_STOP}"))
+        .map(|row: sqlx::postgres::PgRow| OsdrSearchHit {
+            rank: row.get("rank"),
+            snippet: render_snippet(row.get("snippet")),
+            dataset: map_osdr_record(row),
+        })
+        .fetch_all(&self.pool)
+        .await?;
+        Ok((total, hits))
+    }
+
+    /// The stored file listing of a dataset row, or `None` if it was never fetched.
+    pub async fn files(&self, item_id: i64) -> sqlx::Result<Option<(DateTime<Utc>, Option<String>, Vec<OsdrFile>)>> {
+        let Some(row) = sqlx::query(
+            "SELECT files_fetched_at, files_source_url FROM osdr_items
+             WHERE id = $1 AND files_fetched_at IS NOT NULL",
+        )
+        .bind(item_id)
+        .fetch_optional(&self.pool)
+        .await?
+        else {
+            return Ok(None);
+        };
+        let files = sqlx::query(
+            "SELECT file_name, size_bytes, download_url, category
+             FROM osdr_item_files
+             WHERE item_id = $1
+             ORDER BY position",
+        )
+        .bind(item_id)
+        .map(|row: sqlx::postgres::PgRow| OsdrFile {
+            file_name: row.get("file_name"),
+            size_bytes: row.get("size_bytes"),
+            download_url: row.get("download_url"),
+            category: row.get("category"),
+        })
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(Some((row.get("files_fetched_at"), row.get("files_source_url"), files)))
+    }
+
+    /// Swaps in a freshly fetched file listing and stamps the row; returns the stamp.
+    pub async fn replace_files(
+        &self,
+        item_id: i64,
+        source_url: &str,
+        files: &[OsdrFile],
+    ) -> sqlx::Result<DateTime<Utc>> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM osdr_item_files WHERE item_id = $1")
+            .bind(item_id)
+            .execute(&mut *tx)
             .await?;
-        }
-        Ok(())
+        sqlx::query(
+            "INSERT INTO osdr_item_files(item_id, position, file_name, size_bytes, download_url, category)
+             SELECT $1, f.position, f.file_name, f.size_bytes, f.download_url, f.category
+             FROM UNNEST($2::text[], $3::bigint[], $4::text[], $5::text[])
+                  WITH ORDINALITY AS f(file_name, size_bytes, download_url, category, position)",
+        )
+        .bind(item_id)
+        .bind(files.iter().map(|file| file.file_name.clone()).collect::<Vec<_>>())
+        .bind(files.iter().map(|file| file.size_bytes).collect::<Vec<_>>())
+        .bind(files.iter().map(|file| file.download_url.clone()).collect::<Vec<_>>())
+        .bind(files.iter().map(|file| file.category.clone()).collect::<Vec<_>>())
+        .execute(&mut *tx)
+        .await?;
+        let fetched_at = sqlx::query(
+            "UPDATE osdr_items SET files_fetched_at = now(), files_source_url = $2
+             WHERE id = $1
+             RETURNING files_fetched_at",
+        )
+        .bind(item_id)
+        .bind(source_url)
+        .map(|row: sqlx::postgres::PgRow| row.get::<DateTime<Utc>, _>("files_fetched_at"))
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(fetched_at)
+    }
+
+    pub async fn revisions(
+        &self,
+        dataset_id: &str,
+        since: Option<DateTime<Utc>>,
+        limit: i64,
+    ) -> sqlx::Result<Vec<OsdrRevision>> {
+        let rows = sqlx::query(
+            "SELECT id, dataset_id, sync_run_id, kind, recorded_at, previous_updated_at, updated_at,
+                    previous_title, title, previous_status, status, diff
+             FROM osdr_item_revisions
+             WHERE dataset_id = $1
+               AND ($2::timestamptz IS NULL OR recorded_at > $2)
+             ORDER BY recorded_at DESC, id DESC
+             LIMIT $3",
+        )
+        .bind(dataset_id)
+        .bind(since)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                let kind: String = row.get("kind");
+                Some(OsdrRevision {
+                    id: row.get("id"),
+                    dataset_id: row.get("dataset_id"),
+                    sync_run_id: row.get("sync_run_id"),
+                    kind: OsdrRevisionKind::parse(&kind)?,
+                    recorded_at: row.get("recorded_at"),
+                    previous_updated_at: row.get("previous_updated_at"),
+                    updated_at: row.get("updated_at"),
+                    previous_title: row.get("previous_title"),
+                    title: row.get("title"),
+                    previous_status: row.get("previous_status"),
+                    status: row.get("status"),
+                    diff: row.get("diff"),
+                })
+            })
+            .collect())
     }
 
     pub async fn count(&self) -> sqlx::Result<i64> {
@@ -191,25 +1379,51 @@ impl OsdrRepo {
             .await
     }
 
-    pub async fn list(&self, limit: i64) -> sqlx::Result<Vec<OsdrRecord>> {
-        sqlx::query(
-            "SELECT id, dataset_id, title, status, updated_at, inserted_at, raw
+    /// One page of datasets in keyset order. Nullable sort columns are compared through a
+    /// sentinel (`-infinity` / empty string) so rows without a value still page stably;
+    /// `id` breaks ties.
+    pub async fn list(
+        &self,
+        filter: &OsdrListFilter,
+        sort: OsdrSortKey,
+        descending: bool,
+        after: Option<&OsdrCursor>,
+        limit: i64,
+    ) -> sqlx::Result<Vec<OsdrRecord>> {
+        let (column, key) = match sort {
+            OsdrSortKey::InsertedAt => ("inserted_at", "$5::timestamptz"),
+            OsdrSortKey::UpdatedAt => (
+                "coalesce(updated_at, '-infinity'::timestamptz)",
+                "coalesce($5::timestamptz, '-infinity'::timestamptz)",
+            ),
+            OsdrSortKey::DatasetId => ("coalesce(dataset_id, '')", "coalesce($5::text, '')"),
+            OsdrSortKey::Title => ("coalesce(title, '')", "coalesce($5::text, '')"),
+        };
+        let (direction, past) = if descending { ("DESC", "<") } else { ("ASC", ">") };
+        let sql = format!(
+            "SELECT id, dataset_id, title, status, updated_at, inserted_at, last_seen_at, removed_at, raw
              FROM osdr_items
-             ORDER BY inserted_at DESC
-             LIMIT $1",
-        )
-        .bind(limit)
-        .map(|row: sqlx::postgres::PgRow| OsdrRecord {
-            id: row.get("id"),
-            dataset_id: row.get("dataset_id"),
-            title: row.get("title"),
-            status: row.get("status"),
-            updated_at: row.get("updated_at"),
-            inserted_at: row.get("inserted_at"),
-            raw: row.get("raw"),
-        })
-        .fetch_all(&self.pool)
-        .await
+             WHERE ($8::text = 'include' OR ($8::text = 'only') = (removed_at IS NOT NULL))
+               AND ($1::text IS NULL OR status = $1)
+               AND ($2::timestamptz IS NULL OR updated_at >= $2)
+               AND ($3::timestamptz IS NULL OR updated_at <= $3)
+               AND ($4::text IS NULL OR starts_with(dataset_id, $4))
+               AND ($6::bigint IS NULL OR ({column}, id) {past} ({key}, $6))
+             ORDER BY {column} {direction}, id {direction}
+             LIMIT $7"
+        );
+        sqlx::query(&sql)
+            .bind(&filter.status)
+            .bind(filter.updated_from)
+            .bind(filter.updated_to)
+            .bind(&filter.id_prefix)
+            .bind(after.and_then(|cursor| cursor.key.clone()))
+            .bind(after.map(|cursor| cursor.id))
+            .bind(limit)
+            .bind(filter.removed.as_str())
+            .map(map_osdr_record)
+            .fetch_all(&self.pool)
+            .await
     }
 }
 
@@ -260,20 +1474,6 @@ impl CacheRepo {
         .await
     }
 
-    pub async fn latest_with_metadata(&self, source: &str) -> sqlx::Result<Value> {
-        Ok(self
-            .latest(source)
-            .await?
-            .map(|item| {
-                json!({
-                    "source": item.source,
-                    "at": item.fetched_at,
-                    "payload": item.payload
-                })
-            })
-            .unwrap_or_else(|| json!({})))
-    }
-
     pub async fn latest_by_sources(&self, sources: &[String]) -> sqlx::Result<HashMap<String, Value>> {
         if sources.is_empty() {
             return Ok(HashMap::new());
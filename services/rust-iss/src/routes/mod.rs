@@ -1,4 +1,7 @@
-use axum::{routing::get, Router};
+use axum::{
+    routing::{delete, get, post},
+    Router,
+};
 
 use crate::{handlers, services::AppState};
 
@@ -8,8 +11,42 @@ pub fn build_router(state: AppState) -> Router {
         .route("/last", get(handlers::last_iss))
         .route("/fetch", get(handlers::trigger_iss))
         .route("/iss/trend", get(handlers::iss_trend))
+        .route("/iss/track", get(handlers::iss_track))
+        .route("/iss/track.geojson", get(handlers::iss_track_geojson))
+        .route("/iss/track.kml", get(handlers::iss_track_kml))
+        .route("/iss/anomalies", get(handlers::iss_anomalies))
+        .route("/iss/at", get(handlers::iss_at))
+        .route("/iss/orbit/altitude-series", get(handlers::iss_altitude_series))
+        .route("/iss/orbit/events", get(handlers::iss_orbit_events))
+        .route("/iss/orbit/run", get(handlers::iss_orbit_run))
+        .route("/iss/overflights", get(handlers::iss_overflights))
+        .route("/iss/position", get(handlers::iss_position))
+        .route("/iss/passes", get(handlers::iss_passes))
+        .route("/iss/geofences", get(handlers::geofences_list).post(handlers::geofence_create))
+        .route("/iss/geofences/:id", delete(handlers::geofence_delete))
+        .route("/iss/geofences/:id/events", get(handlers::geofence_events))
+        .route("/iss/regions", get(handlers::iss_regions))
+        .route("/iss/sunlight", get(handlers::iss_sunlight))
+        .route("/iss/terminator.geojson", get(handlers::iss_terminator))
+        .route("/iss/rollups/run", get(handlers::iss_rollup_run))
+        .route("/iss/rollups/:period", get(handlers::iss_rollups))
+        .route("/satellites", get(handlers::satellites))
+        .route("/satellites/:id/last", get(handlers::satellite_last))
+        .route("/satellites/:id/trend", get(handlers::satellite_trend))
+        .route("/satellites/:id/track", get(handlers::satellite_track))
+        .route("/satellites/:id/anomalies", get(handlers::satellite_anomalies))
+        .route("/satellites/:id/orbit/altitude-series", get(handlers::satellite_altitude_series))
+        .route("/satellites/:id/orbit/events", get(handlers::satellite_orbit_events))
+        .route("/satellites/:id/overflights", get(handlers::satellite_overflights))
+        .route("/satellites/:id/regions", get(handlers::satellite_regions))
+        .route("/satellites/:id/rollups/:period", get(handlers::satellite_rollups))
         .route("/osdr/sync", get(handlers::osdr_sync))
         .route("/osdr/list", get(handlers::osdr_list))
+        .route("/osdr/search", get(handlers::osdr_search))
+        .route("/osdr/mapping/dry-run", post(handlers::osdr_mapping_dry_run))
+        .route("/osdr/:dataset_id", get(handlers::osdr_dataset))
+        .route("/osdr/:dataset_id/files", get(handlers::osdr_files))
+        .route("/osdr/:dataset_id/history", get(handlers::osdr_history))
         .route("/space/:src/latest", get(handlers::space_latest))
         .route("/space/refresh", get(handlers::space_refresh))
         .route("/space/summary", get(handlers::space_summary))
@@ -1,16 +1,34 @@
-use std::{future::Future, sync::Arc, time::Duration};
+use std::{
+    collections::{BTreeMap, HashSet},
+    future::Future,
+    sync::Arc,
+    time::Duration,
+};
 
-use anyhow::{anyhow, Context, Result};
-use chrono::{DateTime, Datelike, NaiveDateTime, TimeZone, Utc};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, TimeZone, Utc};
 use serde_json::{json, Value};
-use tokio::sync::Mutex;
-use tracing::{error, info};
+use tokio::sync::{Mutex, RwLock};
+use tracing::{error, info, warn};
 
 use crate::{
-    clients::{ExternalClients, IssClient, NasaClient, OsdrClient, SpacexClient},
-    config::AppConfig,
-    domain::{IssEntry, IssTrend, OsdrRecord, RefreshResult, SpaceLatestResponse, SpaceSummary},
-    repo::{CacheRepo, IssRepo, OsdrRepo, Repositories},
+    clients::{self, ExternalClients, IssClient, IssFetch, NasaClient, OsdrClient, SpacexClient, TleClient},
+    config::{AnomalyConfig, AppConfig, OrbitAnalysisConfig, TrackedSatellite},
+    domain::{
+        AltitudeBucket, AltitudeSeriesPoint, GeoRegion, Geofence, GeofenceEvent, GeofenceShape, GeofenceTransition,
+        IssAltitudeSeries, IssAnomaly, IssCrossCheck, IssEntry, IssInterpolatedPosition, IssOverflight,
+        IssOverflights, IssPass, IssPassPrediction, IssPosition, IssRegionTime, IssRollup, IssSnapshot,
+        IssSunlightReport, IssSunlightSample, IssTrack, IssTrackPoint, IssTrend, IssTrendSegment, Observer,
+        OrbitAnalysisReport, OrbitEvent, OrbitEventKind, OsdrCursor, OsdrFile, OsdrFileListing, OsdrListFilter,
+        OsdrListPage, OsdrMappingPreview, OsdrRecord, OsdrRemovedFilter, OsdrRevision, OsdrSearchPage, OsdrSortKey, OsdrSyncReport,
+        PredictedPosition, RefreshResult, RegionDwell, RollupPeriod, RollupReport, SpaceLatestResponse, SpaceSummary,
+        Sunlight, SunlightState, UpsertOutcome, ISS_NORAD_ID,
+    },
+    geo::{self, ReverseGeocoder},
+    orbit::{self, Sgp4, Tle},
+    osdr::{self, FieldMap, FieldProfile},
+    repo::{CacheRepo, GeofenceRepo, IssRepo, OrbitRepo, OsdrRepo, Repositories, RollupRepo},
+    solar,
 };
 
 #[derive(Clone)]
@@ -22,19 +40,58 @@ pub struct AppState {
 #[derive(Clone)]
 pub struct ServiceRegistry {
     pub iss: Arc<IssService>,
+    pub orbit: Arc<OrbitService>,
+    pub rollup: Arc<RollupService>,
+    pub orbit_analysis: Arc<OrbitAnalysisService>,
+    pub geofence: Arc<GeofenceService>,
     pub osdr: Arc<OsdrService>,
     pub space: Arc<SpaceCacheService>,
 }
 
+/// Polls and analyses the tracked satellites (the ISS plus anything in `TRACKED_SATELLITES`).
 pub struct IssService {
     repo: IssRepo,
     client: IssClient,
+    orbit: Arc<OrbitService>,
+    geofence: Arc<GeofenceService>,
+    geocoder: Arc<ReverseGeocoder>,
+    satellites: Vec<TrackedSatellite>,
+    anomaly: AnomalyConfig,
     fetch_lock: Arc<Mutex<()>>,
 }
 
+pub struct OrbitService {
+    client: TleClient,
+    cache_repo: CacheRepo,
+    propagator: RwLock<Option<Arc<Sgp4>>>,
+}
+
+/// Maintains hourly/daily aggregates of the fetch log and prunes raw rows past retention.
+pub struct RollupService {
+    repo: RollupRepo,
+    satellites: Vec<TrackedSatellite>,
+    raw_retention: Option<chrono::Duration>,
+    rollup_lock: Arc<Mutex<()>>,
+}
+
+/// Estimates altitude decay and detects reboost/lowering burns from stored altitudes.
+pub struct OrbitAnalysisService {
+    repo: OrbitRepo,
+    config: OrbitAnalysisConfig,
+    analysis_lock: Arc<Mutex<()>>,
+}
+
+pub struct GeofenceService {
+    repo: GeofenceRepo,
+}
+
 pub struct OsdrService {
     repo: OsdrRepo,
     client: OsdrClient,
+    field_map: Arc<FieldMap>,
+    files_ttl: chrono::Duration,
+    tombstone_grace: chrono::Duration,
+    full_sync_every: chrono::Duration,
     fetch_lock: Arc<Mutex<()>>,
 }
 
@@ -45,7 +102,6 @@ pub struct SpaceCacheService {
     nasa: NasaClient,
     spacex: SpacexClient,
     refresh_lock: Arc<Mutex<()>>,
-    default_sources: Vec<String>,
 }
 
 impl AppState {
@@ -58,117 +114,914 @@ impl AppState {
 }
 
 impl ServiceRegistry {
-    pub fn new(repos: &Repositories, clients: &ExternalClients, config: &AppConfig) -> Self {
+    pub fn new(
+        repos: &Repositories,
+        clients: &ExternalClients,
+        geocoder: Arc<ReverseGeocoder>,
+        field_map: Arc<FieldMap>,
+        config: &AppConfig,
+    ) -> Self {
+        let orbit = Arc::new(OrbitService::new(clients.tle.clone(), repos.cache.clone()));
+        let geofence = Arc::new(GeofenceService::new(repos.geofence.clone()));
         Self {
-            iss: Arc::new(IssService::new(repos.iss.clone(), clients.iss.clone())),
-            osdr: Arc::new(OsdrService::new(repos.osdr.clone(), clients.osdr.clone())),
+            iss: Arc::new(IssService::new(
+                repos.iss.clone(),
+                clients.iss.clone(),
+                orbit.clone(),
+                geofence.clone(),
+                geocoder,
+                config.satellites.clone(),
+                config.anomaly.clone(),
+            )),
+            orbit,
+            rollup: Arc::new(RollupService::new(
+                repos.rollup.clone(),
+                config.satellites.clone(),
+                config.raw_retention_days,
+            )),
+            orbit_analysis: Arc::new(OrbitAnalysisService::new(repos.orbit.clone(), config.orbit_analysis.clone())),
+            geofence,
+            osdr: Arc::new(OsdrService::new(
+                repos.osdr.clone(),
+                clients.osdr.clone(),
+                field_map,
+                config.osdr_files_ttl,
+                config.osdr_tombstone_grace,
+                config.osdr_full_sync_every,
+            )),
             space: Arc::new(SpaceCacheService::new(
                 repos.cache.clone(),
                 repos.iss.clone(),
                 repos.osdr.clone(),
                 clients.nasa.clone(),
                 clients.spacex.clone(),
-                config.refresh_defaults.clone(),
             )),
         }
     }
 }
 
 impl IssService {
-    pub fn new(repo: IssRepo, client: IssClient) -> Self {
+    pub fn new(
+        repo: IssRepo,
+        client: IssClient,
+        orbit: Arc<OrbitService>,
+        geofence: Arc<GeofenceService>,
+        geocoder: Arc<ReverseGeocoder>,
+        satellites: Vec<TrackedSatellite>,
+        anomaly: AnomalyConfig,
+    ) -> Self {
         Self {
             repo,
             client,
+            orbit,
+            geofence,
+            geocoder,
+            satellites,
+            anomaly,
             fetch_lock: Arc::new(Mutex::new(())),
         }
     }
 
-    pub async fn last(&self) -> Result<Option<IssEntry>> {
-        Ok(self.repo.last_entry().await?)
+    pub fn satellites(&self) -> &[TrackedSatellite] {
+        &self.satellites
+    }
+
+    pub fn ensure_tracked(&self, norad_id: i32) -> Result<()> {
+        if self.satellites.iter().any(|sat| sat.norad_id == norad_id) {
+            Ok(())
+        } else {
+            Err(anyhow!("satellite {norad_id} is not tracked"))
+        }
+    }
+
+    pub async fn last(&self, norad_id: i32) -> Result<Option<IssEntry>> {
+        Ok(self.repo.last_entry(norad_id).await?)
+    }
+
+    pub async fn trigger_fetch(&self, norad_id: i32) -> Result<IssSnapshot> {
+        self.fetch_and_store(norad_id).await
+    }
+
+    pub async fn trend(&self, norad_id: i32, limit: i64, include_anomalies: bool) -> Result<IssTrend> {
+        let mut rows = self
+            .repo
+            .last_points(norad_id, limit.max(2), !include_anomalies)
+            .await?;
+        rows.reverse();
+        Ok(compute_trend(&rows))
     }
 
-    pub async fn trigger_fetch(&self) -> Result<IssEntry> {
-        self.fetch_and_store().await
+    pub async fn track(
+        &self,
+        norad_id: i32,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        max_points: usize,
+        fill_step: Option<chrono::Duration>,
+        include_anomalies: bool,
+    ) -> Result<IssTrack> {
+        let mut points = self.repo.track(norad_id, from, to, !include_anomalies).await?;
+        let raw_points = points.len();
+        if let Some(step) = fill_step {
+            // The orbit model is fed with the ISS element set only.
+            if norad_id != ISS_NORAD_ID {
+                return Err(anyhow!("gap filling is only available for the ISS"));
+            }
+            points = self.orbit.fill_gaps(points, step).await?;
+        }
+        Ok(IssTrack {
+            from,
+            to,
+            raw_points,
+            points: downsample_track(points, max_points),
+        })
+    }
+
+    /// Position at `at` interpolated from the stored samples around it; `None` when `at`
+    /// lies outside the recorded history.
+    pub async fn position_at(
+        &self,
+        norad_id: i32,
+        at: DateTime<Utc>,
+        max_gap: chrono::Duration,
+    ) -> Result<Option<IssInterpolatedPosition>> {
+        let (before, after) = self.repo.bracketing(norad_id, at).await?;
+        Ok(match (before, after) {
+            (Some(before), _) if before.at == at => Some(interpolate_between(before.clone(), before, at, max_gap)),
+            (Some(before), Some(after)) => Some(interpolate_between(before, after, at, max_gap)),
+            _ => None,
+        })
+    }
+
+    pub fn region_of(&self, position: &IssPosition) -> GeoRegion {
+        self.geocoder.lookup(position.latitude, position.longitude)
+    }
+
+    /// Aggregates clean samples in the window by the country or ocean underneath them.
+    pub async fn region_time(
+        &self,
+        norad_id: i32,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        max_gap: chrono::Duration,
+    ) -> Result<IssRegionTime> {
+        let points = self.repo.track(norad_id, from, to, true).await?;
+        Ok(dwell_by_region(&points, from, to, max_gap, |lat, lon| {
+            self.geocoder.lookup(lat, lon)
+        }))
+    }
+
+    /// Intervals in `[from, to]` when the sub-satellite point was within `radius_km` of the
+    /// given location. Only samples inside a bounding box around it are loaded, together
+    /// with their direct neighbours, so the cost follows the number of passes rather than
+    /// the length of the window.
+    pub async fn overflights(
+        &self,
+        norad_id: i32,
+        (lat, lon): (f64, f64),
+        radius_km: f64,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        max_gap: chrono::Duration,
+    ) -> Result<IssOverflights> {
+        let max_gap_sec = max_gap.num_milliseconds() as f64 / 1_000.0;
+        // A segment crossing the disc has an end within half its length of the centre, so
+        // widening the box by half the distance covered in `max_gap` cannot miss a pass.
+        let (lat_range, lon_ranges) =
+            overflight_box(lat, lon, radius_km + MAX_GROUND_SPEED_KM_S * max_gap_sec / 2.0);
+        let rows = self.repo.within_box(norad_id, from, to, lat_range, lon_ranges).await?;
+        let candidates = rows.len();
+
+        let mut by_time = BTreeMap::new();
+        let mut links = HashSet::new();
+        for row in rows {
+            if let Some(prev) = row.prev {
+                links.insert((prev.at, row.point.at));
+                by_time.insert(prev.at, prev);
+            }
+            if let Some(next) = row.next {
+                links.insert((row.point.at, next.at));
+                by_time.insert(next.at, next);
+            }
+            by_time.insert(row.point.at, row.point);
+        }
+        let points: Vec<IssTrackPoint> = by_time.into_values().collect();
+        let contiguous: Vec<bool> = points
+            .iter()
+            .enumerate()
+            .map(|(idx, point)| idx > 0 && links.contains(&(points[idx - 1].at, point.at)))
+            .collect();
+
+        let items = find_overflights(&points, &contiguous, (lat, lon), radius_km, max_gap_sec)
+            .into_iter()
+            .filter(|pass| pass.end >= from && pass.start <= to)
+            .collect();
+        Ok(IssOverflights {
+            lat,
+            lon,
+            radius_km,
+            from,
+            to,
+            max_gap_sec,
+            candidates,
+            items,
+        })
+    }
+
+    pub fn sunlight_of(&self, position: &IssPosition, at: DateTime<Utc>) -> Option<Sunlight> {
+        let altitude = position.altitude?;
+        Some(solar::sunlight(position.latitude, position.longitude, altitude, at))
+    }
+
+    pub async fn sunlight(&self, norad_id: i32, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<IssSunlightReport> {
+        let points = self.repo.track(norad_id, from, to, true).await?;
+        let items: Vec<IssSunlightSample> = points
+            .iter()
+            .filter_map(|p| {
+                let altitude = p.altitude?;
+                Some(IssSunlightSample {
+                    at: p.at,
+                    lat: p.lat,
+                    lon: p.lon,
+                    altitude,
+                    sunlight: solar::sunlight(p.lat, p.lon, altitude, p.at),
+                })
+            })
+            .collect();
+        let count = |state: SunlightState| items.iter().filter(|i| i.sunlight.state == state).count();
+        Ok(IssSunlightReport {
+            from,
+            to,
+            samples: items.len(),
+            sunlit: count(SunlightState::Sunlit),
+            penumbra: count(SunlightState::Penumbra),
+            umbra: count(SunlightState::Umbra),
+            items,
+        })
+    }
+
+    pub async fn anomalies(&self, norad_id: i32, limit: i64) -> Result<Vec<IssEntry>> {
+        Ok(self.repo.anomalies(norad_id, limit).await?)
+    }
+
+    /// Polls every tracked satellite; one failing upstream does not block the others.
+    pub async fn fetch_job(&self) -> Result<()> {
+        let mut failed = 0usize;
+        for sat in &self.satellites {
+            match self.fetch_and_store(sat.norad_id).await {
+                Ok(snapshot) if snapshot.duplicate => {
+                    info!("{} snapshot unchanged since {}, skipped", sat.name, snapshot.entry.fetched_at)
+                }
+                Ok(snapshot) => info!("{} snapshot stored at {}", sat.name, snapshot.entry.fetched_at),
+                Err(err) => {
+                    failed += 1;
+                    error!(target: "iss_fetch", norad_id = sat.norad_id, error = %err, "fetch failed");
+                }
+            }
+        }
+        if failed == self.satellites.len() && failed > 0 {
+            return Err(anyhow!("all {failed} satellite fetches failed"));
+        }
+        Ok(())
     }
 
-    pub async fn trend(&self) -> Result<IssTrend> {
-        let rows = self.repo.last_points(2).await?;
-        if rows.len() < 2 {
-            return Ok(IssTrend {
-                movement: false,
-                delta_km: 0.0,
-                dt_sec: 0.0,
-                velocity_kmh: None,
-                from_time: None,
-                to_time: None,
-                from_lat: None,
-                from_lon: None,
-                to_lat: None,
-                to_lon: None,
+    async fn fetch_and_store(&self, norad_id: i32) -> Result<IssSnapshot> {
+        let _guard = self.fetch_lock.lock().await;
+        let fetched = self.client.fetch(norad_id).await?;
+        let position = IssPosition::from_payload(&fetched.payload);
+        let observed_at = position.as_ref().and_then(|p| p.observed_at);
+        if let Some(entry) = self
+            .repo
+            .find_duplicate_of_last(norad_id, observed_at, &fetched.payload)
+            .await?
+        {
+            return Ok(IssSnapshot {
+                duplicate: true,
+                entry,
+                cross_check: None,
             });
         }
-        Ok(compute_trend(&rows[1], &rows[0]))
+        let recent = self.repo.last_points(norad_id, 5, false).await?;
+        let mut anomalies = detect_anomalies(position.as_ref(), Utc::now(), &recent, &self.anomaly);
+        let cross_check = match (self.anomaly.cross_check_km, position.as_ref()) {
+            (Some(max_km), Some(position)) => self.cross_check(norad_id, &fetched, position, max_km).await,
+            _ => None,
+        };
+        if cross_check.as_ref().is_some_and(|check| !check.agrees) {
+            anomalies.push(IssAnomaly::ProviderDisagreement);
+        }
+        let source_url = fetched.source_url;
+        if !anomalies.is_empty() {
+            warn!(norad_id, ?anomalies, "satellite snapshot from {source_url} flagged");
+        }
+        let entry = self
+            .repo
+            .insert_entry(norad_id, &source_url, position.as_ref(), &anomalies, fetched.payload)
+            .await?;
+        if anomalies.is_empty() {
+            if let Err(err) = self.geofence.evaluate(&entry).await {
+                warn!(norad_id, error = %err, "geofence evaluation failed for entry {}", entry.id);
+            }
+        }
+        Ok(IssSnapshot {
+            duplicate: false,
+            entry,
+            cross_check,
+        })
+    }
+
+    /// Compares a fresh snapshot with the next provider. A failing or position-less second
+    /// provider only skips the check; it never blocks storing the primary snapshot.
+    async fn cross_check(
+        &self,
+        norad_id: i32,
+        primary: &IssFetch,
+        position: &IssPosition,
+        max_km: f64,
+    ) -> Option<IssCrossCheck> {
+        let secondary = match self.client.fetch_secondary(norad_id, primary.provider).await? {
+            Ok(secondary) => secondary,
+            Err(err) => {
+                warn!(norad_id, error = %err, "cross-check provider failed");
+                return None;
+            }
+        };
+        let other = IssPosition::from_payload(&secondary.payload)?;
+        let check = compare_positions(position, &other, max_km);
+        if !check.agrees {
+            warn!(
+                norad_id,
+                "{} and {} disagree by {:.1} km (allowed {:.1} km)",
+                primary.provider.as_str(),
+                secondary.provider.as_str(),
+                check.distance_km,
+                check.allowed_km
+            );
+        }
+        Some(IssCrossCheck {
+            provider: secondary.provider.as_str().to_string(),
+            source_url: secondary.source_url,
+            ..check
+        })
+    }
+}
+
+impl OrbitService {
+    pub fn new(client: TleClient, cache_repo: CacheRepo) -> Self {
+        Self {
+            client,
+            cache_repo,
+            propagator: RwLock::new(None),
+        }
+    }
+
+    pub async fn refresh(&self) -> Result<Arc<Sgp4>> {
+        let tle = self.client.fetch().await?;
+        let sgp4 = Arc::new(Sgp4::from_tle(&tle)?);
+        self.cache_repo
+            .write(
+                "tle",
+                json!({ "name": tle.name, "line1": tle.line1, "line2": tle.line2 }),
+            )
+            .await?;
+        *self.propagator.write().await = Some(sgp4.clone());
+        Ok(sgp4)
+    }
+
+    /// Returns the current propagator, falling back to the last cached TLE and finally
+    /// to a fresh download.
+    pub async fn propagator(&self) -> Result<Arc<Sgp4>> {
+        if let Some(sgp4) = self.propagator.read().await.clone() {
+            return Ok(sgp4);
+        }
+        if let Some(item) = self.cache_repo.latest("tle").await? {
+            let tle = Tle {
+                name: pick_string(&item.payload, &["name"]),
+                line1: pick_string(&item.payload, &["line1"]).unwrap_or_default(),
+                line2: pick_string(&item.payload, &["line2"]).unwrap_or_default(),
+            };
+            if let Ok(sgp4) = Sgp4::from_tle(&tle) {
+                let sgp4 = Arc::new(sgp4);
+                *self.propagator.write().await = Some(sgp4.clone());
+                return Ok(sgp4);
+            }
+        }
+        self.refresh().await
+    }
+
+    pub async fn position_at(&self, at: DateTime<Utc>) -> Result<PredictedPosition> {
+        let sgp4 = self.propagator().await?;
+        predict_position(&sgp4, at)
+    }
+
+    pub async fn passes(&self, observer: Observer, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<IssPassPrediction> {
+        let sgp4 = self.propagator().await?;
+        let passes = find_passes(&sgp4, &observer, from, to)?;
+        Ok(IssPassPrediction {
+            observer,
+            from,
+            to,
+            tle_epoch: sgp4.epoch,
+            passes,
+        })
+    }
+
+    /// Inserts propagated points into every gap between stored samples longer than `step`.
+    pub async fn fill_gaps(&self, points: Vec<IssTrackPoint>, step: chrono::Duration) -> Result<Vec<IssTrackPoint>> {
+        if points.len() < 2 || step <= chrono::Duration::zero() {
+            return Ok(points);
+        }
+        let sgp4 = self.propagator().await?;
+        let mut out = Vec::with_capacity(points.len());
+        for pair in points.windows(2) {
+            out.push(pair[0].clone());
+            let mut at = pair[0].at + step;
+            while at + step / 2 < pair[1].at {
+                let predicted = predict_position(&sgp4, at)?;
+                out.push(IssTrackPoint {
+                    at,
+                    lat: predicted.latitude,
+                    lon: predicted.longitude,
+                    altitude: Some(predicted.altitude),
+                    velocity: Some(predicted.velocity),
+                    propagated: true,
+                });
+                at += step;
+            }
+        }
+        out.extend(points.last().cloned());
+        Ok(out)
     }
 
     pub async fn fetch_job(&self) -> Result<()> {
-        let entry = self.fetch_and_store().await?;
-        info!("iss snapshot stored at {}", entry.fetched_at);
+        let sgp4 = self.refresh().await?;
+        info!("tle refreshed, epoch {}", sgp4.epoch);
         Ok(())
     }
+}
 
-    fn source_url(&self) -> &str {
-        self.client.url()
+impl RollupService {
+    pub fn new(repo: RollupRepo, satellites: Vec<TrackedSatellite>, raw_retention_days: Option<i64>) -> Self {
+        Self {
+            repo,
+            satellites,
+            raw_retention: raw_retention_days.map(chrono::Duration::days),
+            rollup_lock: Arc::new(Mutex::new(())),
+        }
     }
 
-    async fn fetch_and_store(&self) -> Result<IssEntry> {
-        let _guard = self.fetch_lock.lock().await;
-        let payload = self.client.fetch().await?;
-        let entry = self.repo.insert_entry(self.source_url(), payload).await?;
-        Ok(entry)
+    pub async fn list(
+        &self,
+        period: RollupPeriod,
+        norad_id: i32,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<IssRollup>> {
+        Ok(self.repo.list(period, norad_id, period.bucket_start(from), to).await?)
+    }
+
+    /// Rolls up every tracked satellite, then prunes raw history. Pruning runs only after
+    /// both rollups succeeded, so a failed aggregation never loses data.
+    pub async fn run(&self) -> Result<RollupReport> {
+        let _guard = self.rollup_lock.lock().await;
+        let mut report = RollupReport::default();
+        for sat in &self.satellites {
+            report.hourly_buckets += self.repo.rollup(RollupPeriod::Hourly, sat.norad_id).await?;
+            report.daily_buckets += self.repo.rollup(RollupPeriod::Daily, sat.norad_id).await?;
+        }
+        if let Some(retention) = self.raw_retention {
+            report.pruned_rows = self.repo.prune_raw(prune_cutoff(Utc::now(), retention)).await?;
+        }
+        Ok(report)
+    }
+
+    pub async fn rollup_job(&self) -> Result<()> {
+        let report = self.run().await?;
+        info!(
+            "iss rollup: {} hourly / {} daily buckets, {} raw rows pruned",
+            report.hourly_buckets, report.daily_buckets, report.pruned_rows
+        );
+        Ok(())
+    }
+}
+
+/// Raw rows are pruned by whole UTC days, so a day is never left half rolled up and
+/// half raw: the cutoff is the start of the day `retention` ago.
+fn prune_cutoff(now: DateTime<Utc>, retention: chrono::Duration) -> DateTime<Utc> {
+    RollupPeriod::Daily.bucket_start(now - retention)
+}
+
+/// Altitude buckets for burn detection span roughly one ISS orbit, which averages out
+/// the altitude swing along the orbit.
+pub const ORBIT_BUCKET_SEC: i64 = 5_580;
+
+impl OrbitAnalysisService {
+    pub fn new(repo: OrbitRepo, config: OrbitAnalysisConfig) -> Self {
+        Self {
+            repo,
+            config,
+            analysis_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    pub async fn events(
+        &self,
+        norad_id: i32,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<OrbitEvent>> {
+        Ok(self.repo.events(norad_id, from, to, limit).await?)
+    }
+
+    pub async fn altitude_series(
+        &self,
+        norad_id: i32,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        bucket_sec: i64,
+        window: chrono::Duration,
+    ) -> Result<IssAltitudeSeries> {
+        let buckets = usable_buckets(self.repo.altitude_buckets(norad_id, from, to, bucket_sec).await?);
+        let events = self.repo.events(norad_id, from - window, to, 1_000).await?;
+        let resets: Vec<DateTime<Utc>> = events.iter().map(|e| e.at).collect();
+        let decay = rolling_decay(&buckets, &resets, window);
+        let current_decay_km_per_day = decay.iter().rev().flatten().next().copied();
+        Ok(IssAltitudeSeries {
+            norad_id,
+            from,
+            to,
+            bucket_sec,
+            window_hours: window.num_hours(),
+            current_decay_km_per_day,
+            events: events.into_iter().filter(|e| e.at >= from).collect(),
+            points: buckets
+                .into_iter()
+                .zip(decay)
+                .map(|(bucket, decay_km_per_day)| AltitudeSeriesPoint {
+                    bucket,
+                    decay_km_per_day,
+                })
+                .collect(),
+        })
+    }
+
+    /// Re-examines the ISS lookback window and stores new burns. The buckets are one ISS
+    /// orbit long, so other tracked satellites are left out rather than misread.
+    pub async fn run(&self) -> Result<OrbitAnalysisReport> {
+        let _guard = self.analysis_lock.lock().await;
+        let to = Utc::now();
+        let from = to - chrono::Duration::days(self.config.lookback_days);
+        let dedupe = chrono::Duration::seconds(ORBIT_BUCKET_SEC * self.config.reboost_span as i64);
+        let buckets = usable_buckets(self.repo.altitude_buckets(ISS_NORAD_ID, from, to, ORBIT_BUCKET_SEC).await?);
+        let events = detect_altitude_steps(
+            ISS_NORAD_ID,
+            &buckets,
+            ORBIT_BUCKET_SEC,
+            self.config.reboost_span,
+            self.config.reboost_min_km,
+        );
+        let mut report = OrbitAnalysisReport {
+            buckets: buckets.len(),
+            detected: events.len(),
+            ..OrbitAnalysisReport::default()
+        };
+        for event in &events {
+            if self.repo.insert_event(event, dedupe).await? {
+                report.stored += 1;
+                info!("ISS {} detected at {}: {:+.2} km", event.kind.as_str(), event.at, event.delta_km);
+            }
+        }
+        Ok(report)
+    }
+
+    pub async fn analysis_job(&self) -> Result<()> {
+        let report = self.run().await?;
+        info!(
+            "orbit analysis: {} buckets, {} burns detected, {} new",
+            report.buckets, report.detected, report.stored
+        );
+        Ok(())
+    }
+}
+
+impl GeofenceService {
+    pub fn new(repo: GeofenceRepo) -> Self {
+        Self { repo }
+    }
+
+    pub async fn create(&self, name: &str, shape: GeofenceShape) -> Result<Geofence> {
+        validate_geofence(name, &shape)?;
+        Ok(self.repo.create(name.trim(), &shape).await?)
+    }
+
+    pub async fn list(&self) -> Result<Vec<Geofence>> {
+        Ok(self.repo.list().await?)
+    }
+
+    pub async fn get(&self, id: i64) -> Result<Option<Geofence>> {
+        Ok(self.repo.get(id).await?)
+    }
+
+    pub async fn delete(&self, id: i64) -> Result<bool> {
+        Ok(self.repo.delete(id).await?)
+    }
+
+    pub async fn events(
+        &self,
+        id: i64,
+        norad_id: Option<i32>,
+        since: Option<DateTime<Utc>>,
+        limit: i64,
+    ) -> Result<Vec<GeofenceEvent>> {
+        Ok(self.repo.events(id, norad_id, since, limit).await?)
+    }
+
+    /// Records enter/exit events for every geofence whose containment of the satellite
+    /// changed since its last recorded transition. Returns the number of events written.
+    pub async fn evaluate(&self, entry: &IssEntry) -> Result<usize> {
+        let Some(position) = entry.position.as_ref() else {
+            return Ok(0);
+        };
+        let fences = self.repo.list().await?;
+        if fences.is_empty() {
+            return Ok(0);
+        }
+        let last = self.repo.last_transitions(entry.norad_id).await?;
+        let mut written = 0;
+        for fence in fences {
+            let was_inside = last.get(&fence.id) == Some(&GeofenceTransition::Enter);
+            let inside = geofence_contains(&fence.shape, position.latitude, position.longitude);
+            let kind = match (was_inside, inside) {
+                (false, true) => GeofenceTransition::Enter,
+                (true, false) => GeofenceTransition::Exit,
+                _ => continue,
+            };
+            self.repo.insert_event(fence.id, kind, entry, position).await?;
+            info!(norad_id = entry.norad_id, "{} geofence {} ({})", kind.as_str(), fence.id, fence.name);
+            written += 1;
+        }
+        Ok(written)
     }
 }
 
 impl OsdrService {
-    pub fn new(repo: OsdrRepo, client: OsdrClient) -> Self {
+    pub fn new(
+        repo: OsdrRepo,
+        client: OsdrClient,
+        field_map: Arc<FieldMap>,
+        files_ttl: Duration,
+        tombstone_grace: Duration,
+        full_sync_every: Duration,
+    ) -> Self {
         Self {
             repo,
             client,
+            field_map,
+            files_ttl: chrono::Duration::from_std(files_ttl).unwrap_or_else(|_| chrono::Duration::days(1)),
+            tombstone_grace: chrono::Duration::from_std(tombstone_grace).unwrap_or_else(|_| chrono::Duration::days(3)),
+            full_sync_every: chrono::Duration::from_std(full_sync_every).unwrap_or_else(|_| chrono::Duration::days(1)),
             fetch_lock: Arc::new(Mutex::new(())),
         }
     }
 
-    pub async fn sync(&self) -> Result<usize> {
+    /// Fetches datasets changed since the stored watermark when the upstream can filter,
+    /// otherwise everything, and writes only the ones whose payload changed. The watermark
+    /// advances to the newest `updated_at` seen, unless the crawl was cut short. Each run
+    /// is logged in `osdr_sync_runs`, and the revisions it writes point back to it.
+    /// Every dataset returned is stamped as seen; after a full (non-incremental, complete)
+    /// crawl, datasets unseen for longer than `OSDR_TOMBSTONE_GRACE_SECONDS` are marked
+    /// removed. A full crawl is forced by `full` or once `OSDR_FULL_SYNC_EVERY_SECONDS`
+    /// have passed since the last one.
+    pub async fn sync(&self, full: bool) -> Result<OsdrSyncReport> {
         let _guard = self.fetch_lock.lock().await;
-        let items = self.client.fetch().await?;
-        let mut written = 0usize;
-        for item in items {
-            let dataset_id = pick_string(&item, &["dataset_id", "id", "uuid", "studyId", "accession", "osdr_id"]);
-            let title = pick_string(&item, &["title", "name", "label"]);
-            let status = pick_string(&item, &["status", "state", "lifecycle"]);
-            let updated = pick_datetime(&item, &["updated", "updated_at", "modified", "lastUpdated", "timestamp"]);
+        let started = Utc::now();
+        let source = self.client.source().to_string();
+        let run_id = self.repo.start_run(&source).await?;
+        let (watermark, last_full_at) = self.repo.sync_state(&source).await?;
+        let since = sync_since(watermark, last_full_at, full, started, self.full_sync_every);
+        let fetched = self.client.fetch(since).await?;
+        let mut report = OsdrSyncReport {
+            run_id,
+            source,
+            incremental: since.is_some() && self.client.supports_since(),
+            since,
+            watermark: since,
+            pages: fetched.pages,
+            items_seen: fetched.items.len(),
+            truncated: fetched.truncated,
+            ..OsdrSyncReport::default()
+        };
+        let (_, profile) = self.field_map.profile(&report.source);
+        let mut seen = Vec::new();
+        for item in fetched.items {
+            let mapped = profile.apply(&item);
+            // `dataset_id` is always required, so only skipped items come without one.
+            let (None, Some(dataset_id)) = (&mapped.skipped, mapped.dataset_id) else {
+                let reason = mapped.skipped.as_deref().unwrap_or("required field dataset_id not found");
+                warn!(reason = %reason, "osdr item skipped by field mapping");
+                report.skipped += 1;
+                continue;
+            };
+            report.watermark = report.watermark.max(mapped.updated_at);
+            seen.push(dataset_id.clone());
+            match self
+                .repo
+                .upsert_item(
+                    Some(run_id),
+                    dataset_id,
+                    mapped.title,
+                    mapped.status,
+                    mapped.updated_at,
+                    item,
+                )
+                .await?
+            {
+                UpsertOutcome::Inserted => report.inserted += 1,
+                UpsertOutcome::Updated => report.updated += 1,
+                UpsertOutcome::Unchanged => report.unchanged += 1,
+            }
+        }
+        report.restored = self.repo.mark_seen(run_id, &seen).await? as usize;
+        if !report.truncated {
             self.repo
-                .upsert_item(dataset_id, title, status, updated, item)
+                .save_watermark(&report.source, report.watermark, !report.incremental)
                 .await?;
-            written += 1;
         }
-        Ok(written)
+        // An empty crawl is far more likely an upstream hiccup than every dataset vanishing.
+        if !report.incremental && !report.truncated && !seen.is_empty() {
+            report.removed = self.repo.tombstone(run_id, started - self.tombstone_grace).await? as usize;
+        }
+        self.repo.finish_run(run_id, &report).await?;
+        Ok(report)
     }
 
-    pub async fn list(&self, limit: i64) -> Result<Vec<OsdrRecord>> {
-        Ok(self.repo.list(limit).await?)
+    /// A page of datasets. When a cursor is given it fixes the sort, so follow-up pages
+    /// only need the same filters; `next_cursor` is set while a full page came back.
+    pub async fn list(
+        &self,
+        filter: &OsdrListFilter,
+        sort: OsdrSortKey,
+        descending: bool,
+        after: Option<&OsdrCursor>,
+        limit: i64,
+    ) -> Result<OsdrListPage> {
+        let (sort, descending) = after.map_or((sort, descending), |cursor| (cursor.sort, cursor.descending));
+        let mut items = self.repo.list(filter, sort, descending, after, limit + 1).await?;
+        let more = items.len() as i64 > limit;
+        items.truncate(limit as usize);
+        let next_cursor = items
+            .last()
+            .filter(|_| more)
+            .map(|last| osdr::encode_cursor(&osdr::cursor_after(last, sort, descending)));
+        Ok(OsdrListPage { items, next_cursor })
     }
 
-    pub async fn count(&self) -> Result<i64> {
-        Ok(self.repo.count().await?)
+    pub async fn search(
+        &self,
+        q: &str,
+        removed: OsdrRemovedFilter,
+        limit: i64,
+        offset: i64,
+    ) -> Result<OsdrSearchPage> {
+        let (total, items) = self.repo.search(q, removed, limit, offset).await?;
+        Ok(OsdrSearchPage {
+            q: q.to_string(),
+            total,
+            limit,
+            offset,
+            items,
+        })
+    }
+
+    /// Shows how items would be mapped without storing anything. `mapping` tries a
+    /// candidate profile instead of the configured one; `payload` is a single item, an
+    /// array of items or a whole list response.
+    pub fn dry_run(
+        &self,
+        source: Option<String>,
+        mapping: Option<Value>,
+        payload: Value,
+    ) -> Result<OsdrMappingPreview> {
+        let source = source.unwrap_or_else(|| self.client.source().to_string());
+        let (name, profile) = match mapping {
+            Some(mapping) => ("request".to_string(), FieldProfile::from_value(mapping)?),
+            None => {
+                let (name, profile) = self.field_map.profile(&source);
+                (name.to_string(), profile.clone())
+            }
+        };
+        let items = clients::page_items(payload)
+            .iter()
+            .map(|item| profile.apply(item))
+            .collect();
+        Ok(OsdrMappingPreview {
+            source,
+            profile: name,
+            mapping: json!(profile),
+            items,
+        })
+    }
+
+    pub async fn get(&self, dataset_id: &str, removed: OsdrRemovedFilter) -> Result<Option<OsdrRecord>> {
+        let record = self.repo.get(dataset_id).await?;
+        Ok(record.filter(|record| removed.matches(record.removed_at)))
+    }
+
+    /// File listing of a stored dataset, served from the database while younger than
+    /// `OSDR_FILES_TTL_SECONDS` unless `refresh` is set. If the upstream call fails an
+    /// older listing is returned marked `stale`. `None` when the dataset is unknown or
+    /// excluded by `removed`.
+    pub async fn files(
+        &self,
+        dataset_id: &str,
+        removed: OsdrRemovedFilter,
+        refresh: bool,
+    ) -> Result<Option<OsdrFileListing>> {
+        let Some(record) = self.get(dataset_id, removed).await? else {
+            return Ok(None);
+        };
+        let stored = self.repo.files(record.id).await?;
+        let listing = |(fetched_at, source_url, files): (DateTime<Utc>, Option<String>, Vec<OsdrFile>), stale| {
+            OsdrFileListing {
+                dataset_id: dataset_id.to_string(),
+                source_url,
+                fetched_at,
+                cached: true,
+                stale,
+                files,
+            }
+        };
+        let fresh = stored
+            .as_ref()
+            .is_some_and(|(fetched_at, ..)| !refresh && Utc::now() - *fetched_at < self.files_ttl);
+        if fresh {
+            return Ok(stored.map(|stored| listing(stored, false)));
+        }
+        match self.client.files(dataset_id).await {
+            Ok((source_url, files)) => {
+                let fetched_at = self.repo.replace_files(record.id, &source_url, &files).await?;
+                Ok(Some(OsdrFileListing {
+                    dataset_id: dataset_id.to_string(),
+                    source_url: Some(source_url),
+                    fetched_at,
+                    cached: false,
+                    stale: false,
+                    files,
+                }))
+            }
+            Err(err) => match stored {
+                Some(stored) => {
+                    warn!(dataset_id, error = %err, "osdr file listing refresh failed, serving stored copy");
+                    Ok(Some(listing(stored, true)))
+                }
+                None => Err(err),
+            },
+        }
+    }
+
+    pub async fn history(
+        &self,
+        dataset_id: &str,
+        since: Option<DateTime<Utc>>,
+        limit: i64,
+    ) -> Result<Vec<OsdrRevision>> {
+        Ok(self.repo.revisions(dataset_id, since, limit).await?)
     }
 
     pub async fn fetch_job(&self) -> Result<()> {
-        let count = self.sync().await?;
-        info!("osdr synced {count} records");
+        let report = self.sync(false).await?;
+        info!(
+            "osdr sync{}: {} inserted, {} updated, {} unchanged, {} skipped, {} removed, {} restored \
+             from {} items over {} pages{}",
+            if report.incremental { " (incremental)" } else { "" },
+            report.inserted,
+            report.updated,
+            report.unchanged,
+            report.skipped,
+            report.removed,
+            report.restored,
+            report.items_seen,
+            report.pages,
+            if report.truncated { " (page limit reached)" } else { "" }
+        );
         Ok(())
     }
 }
 
+/// Watermark to sync from, or `None` for a full crawl: when one is requested, when none
+/// has completed yet, or when the last one is `full_sync_every` old.
+fn sync_since(
+    watermark: Option<DateTime<Utc>>,
+    last_full_at: Option<DateTime<Utc>>,
+    full: bool,
+    now: DateTime<Utc>,
+    full_sync_every: chrono::Duration,
+) -> Option<DateTime<Utc>> {
+    let full_due = last_full_at.is_none_or(|at| now - at >= full_sync_every);
+    watermark.filter(|_| !full && !full_due)
+}
+
 impl SpaceCacheService {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
@@ -177,7 +1030,6 @@ impl SpaceCacheService {
         osdr_repo: OsdrRepo,
         nasa: NasaClient,
         spacex: SpacexClient,
-        default_sources: Vec<String>,
     ) -> Self {
         Self {
             cache_repo,
@@ -186,14 +1038,9 @@ impl SpaceCacheService {
             nasa,
             spacex,
             refresh_lock: Arc::new(Mutex::new(())),
-            default_sources,
         }
     }
 
-    pub fn default_sources(&self) -> Vec<String> {
-        self.default_sources.clone()
-    }
-
     pub async fn latest(&self, source: &str) -> Result<SpaceLatestResponse> {
         let source = source.to_lowercase();
         if let Some(item) = self.cache_repo.latest(&source).await? {
@@ -237,7 +1084,7 @@ impl SpaceCacheService {
         let cme = latest.get("cme").cloned().unwrap_or_else(|| json!({}));
         let spacex = latest.get("spacex").cloned().unwrap_or_else(|| json!({}));
 
-        let iss_json = if let Some(last) = self.iss_repo.last_entry().await? {
+        let iss_json = if let Some(last) = self.iss_repo.last_entry(ISS_NORAD_ID).await? {
             json!({ "at": last.fetched_at, "payload": last.payload })
         } else {
             json!({})
@@ -255,11 +1102,6 @@ impl SpaceCacheService {
         })
     }
 
-    pub async fn refresh_defaults(&self) -> Result<RefreshResult> {
-        let sources = self.default_sources();
-        self.refresh_sources(&sources).await
-    }
-
     async fn refresh_source(&self, source: &str) -> Result<()> {
         match source {
             "apod" => {
@@ -303,6 +1145,30 @@ pub fn spawn_jobs(state: &AppState) {
         }
     });
 
+    spawn_periodic("tle_refresh", schedule.tle, {
+        let orbit = services.orbit.clone();
+        move || {
+            let orbit = orbit.clone();
+            async move { orbit.fetch_job().await }
+        }
+    });
+
+    spawn_periodic("iss_rollup", schedule.rollup, {
+        let rollup = services.rollup.clone();
+        move || {
+            let rollup = rollup.clone();
+            async move { rollup.rollup_job().await }
+        }
+    });
+
+    spawn_periodic("iss_orbit_analysis", schedule.orbit_analysis, {
+        let analysis = services.orbit_analysis.clone();
+        move || {
+            let analysis = analysis.clone();
+            async move { analysis.analysis_job().await }
+        }
+    });
+
     spawn_periodic("osdr_sync", schedule.osdr, {
         let osdr = services.osdr.clone();
         move || {
@@ -371,12 +1237,292 @@ where
     });
 }
 
-fn compute_trend(previous: &IssEntry, newest: &IssEntry) -> IssTrend {
-    let lat1 = newest.payload.get("latitude").and_then(Value::as_f64);
-    let lon1 = newest.payload.get("longitude").and_then(Value::as_f64);
-    let lat0 = previous.payload.get("latitude").and_then(Value::as_f64);
-    let lon0 = previous.payload.get("longitude").and_then(Value::as_f64);
-    let velocity = newest.payload.get("velocity").and_then(Value::as_f64);
+fn predict_position(sgp4: &Sgp4, at: DateTime<Utc>) -> Result<PredictedPosition> {
+    let state = sgp4.propagate(at)?;
+    let geo = state.geodetic(at);
+    Ok(PredictedPosition {
+        at,
+        latitude: geo.latitude,
+        longitude: geo.longitude,
+        altitude: geo.altitude_km,
+        velocity: state.speed_kmh(),
+        tle_epoch: sgp4.epoch,
+    })
+}
+
+const PASS_SCAN_STEP_SEC: i64 = 30;
+const VISIBLE_MIN_ELEVATION: f64 = 10.0;
+const CIVIL_TWILIGHT_SUN_ELEVATION: f64 = -6.0;
+
+/// Samples elevation on a coarse grid, then refines horizon crossings by bisection and
+/// the culmination by golden-section search.
+fn find_passes(sgp4: &Sgp4, observer: &Observer, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Vec<IssPass>> {
+    let site = Site::new(observer);
+    let step = chrono::Duration::seconds(PASS_SCAN_STEP_SEC);
+    let mut passes = Vec::new();
+    let mut rise: Option<DateTime<Utc>> = None;
+    let mut t = from;
+    let mut prev_el = site.elevation(sgp4, t)?;
+    if prev_el >= 0.0 {
+        rise = Some(from);
+    }
+    let mut peak = (from, prev_el);
+
+    while t < to {
+        let next = (t + step).min(to);
+        let el = site.elevation(sgp4, next)?;
+        if prev_el < 0.0 && el >= 0.0 {
+            rise = Some(site.crossing(sgp4, t, next)?);
+            peak = (next, el);
+        } else if el > peak.1 {
+            peak = (next, el);
+        }
+        if let Some(rise_time) = rise {
+            if prev_el >= 0.0 && el < 0.0 {
+                let set_time = site.crossing(sgp4, t, next)?;
+                passes.push(site.describe_pass(sgp4, rise_time, peak.0, set_time, step)?);
+                rise = None;
+            }
+        }
+        prev_el = el;
+        t = next;
+    }
+    Ok(passes)
+}
+
+struct Site {
+    lat: f64,
+    lon: f64,
+    ecef: [f64; 3],
+}
+
+impl Site {
+    fn new(observer: &Observer) -> Self {
+        Self {
+            lat: observer.lat,
+            lon: observer.lon,
+            ecef: orbit::geodetic_to_ecef(observer.lat, observer.lon, observer.alt_m / 1_000.0),
+        }
+    }
+
+    fn look(&self, sgp4: &Sgp4, at: DateTime<Utc>) -> Result<orbit::LookAngles> {
+        let state = sgp4.propagate(at)?;
+        Ok(orbit::look_angles(self.lat, self.lon, self.ecef, state.ecef(at)))
+    }
+
+    fn elevation(&self, sgp4: &Sgp4, at: DateTime<Utc>) -> Result<f64> {
+        let state = sgp4.propagate(at)?;
+        let geo = state.geodetic(at);
+        // Outside the footprint the satellite is below the horizon; skip the topocentric maths.
+        let footprint_km = 6371.0 * (6371.0 / (6371.0 + geo.altitude_km)).acos();
+        if haversine_km(self.lat, self.lon, geo.latitude, geo.longitude) > footprint_km + 100.0 {
+            return Ok(-90.0);
+        }
+        Ok(orbit::look_angles(self.lat, self.lon, self.ecef, state.ecef(at)).elevation)
+    }
+
+    fn crossing(&self, sgp4: &Sgp4, mut lo: DateTime<Utc>, mut hi: DateTime<Utc>) -> Result<DateTime<Utc>> {
+        let lo_above = self.elevation(sgp4, lo)? >= 0.0;
+        while hi - lo > chrono::Duration::milliseconds(500) {
+            let mid = lo + (hi - lo) / 2;
+            if (self.elevation(sgp4, mid)? >= 0.0) == lo_above {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        Ok(lo + (hi - lo) / 2)
+    }
+
+    fn culmination(&self, sgp4: &Sgp4, around: DateTime<Utc>, step: chrono::Duration) -> Result<DateTime<Utc>> {
+        const INV_PHI: f64 = 0.618_033_988_749_895;
+        let mut lo = (around - step).timestamp_millis() as f64;
+        let mut hi = (around + step).timestamp_millis() as f64;
+        let at = |ms: f64| Utc.timestamp_millis_opt(ms as i64).single().unwrap_or(around);
+        while hi - lo > 500.0 {
+            let a = hi - (hi - lo) * INV_PHI;
+            let b = lo + (hi - lo) * INV_PHI;
+            if self.elevation(sgp4, at(a))? < self.elevation(sgp4, at(b))? {
+                lo = a;
+            } else {
+                hi = b;
+            }
+        }
+        Ok(at((lo + hi) / 2.0))
+    }
+
+    fn sun_elevation(&self, at: DateTime<Utc>) -> f64 {
+        let sun = orbit::teme_to_ecef(orbit::sun_position_teme(at), at);
+        orbit::look_angles(self.lat, self.lon, self.ecef, sun).elevation
+    }
+
+    fn describe_pass(
+        &self,
+        sgp4: &Sgp4,
+        rise_time: DateTime<Utc>,
+        peak_guess: DateTime<Utc>,
+        set_time: DateTime<Utc>,
+        step: chrono::Duration,
+    ) -> Result<IssPass> {
+        let culmination_time = self
+            .culmination(sgp4, peak_guess, step)?
+            .clamp(rise_time, set_time);
+        let rise = self.look(sgp4, rise_time)?;
+        let top = self.look(sgp4, culmination_time)?;
+        let set = self.look(sgp4, set_time)?;
+
+        let mut sunlit = false;
+        for at in [rise_time, culmination_time, set_time] {
+            let sat = sgp4.propagate(at)?.geodetic(at);
+            let light = solar::sunlight(sat.latitude, sat.longitude, sat.altitude_km, at);
+            sunlit |= light.state != SunlightState::Umbra;
+        }
+        let observer_dark = self.sun_elevation(culmination_time) < CIVIL_TWILIGHT_SUN_ELEVATION;
+
+        Ok(IssPass {
+            rise_time,
+            rise_azimuth: rise.azimuth,
+            culmination_time,
+            culmination_azimuth: top.azimuth,
+            max_elevation: top.elevation,
+            culmination_range_km: top.range_km,
+            set_time,
+            set_azimuth: set.azimuth,
+            duration_sec: seconds_between(rise_time, set_time),
+            sunlit,
+            observer_dark,
+            visible: sunlit && observer_dark && top.elevation >= VISIBLE_MIN_ELEVATION,
+        })
+    }
+}
+
+/// Checks a new snapshot against the most recent rows (newest first). Motion checks
+/// compare with the newest clean row so one bad sample does not poison the next.
+fn detect_anomalies(
+    position: Option<&IssPosition>,
+    fetched_at: DateTime<Utc>,
+    recent: &[IssEntry],
+    limits: &AnomalyConfig,
+) -> Vec<IssAnomaly> {
+    let Some(position) = position else {
+        return vec![IssAnomaly::MissingPosition];
+    };
+    let mut flags = Vec::new();
+
+    if let Some(observed) = position.observed_at {
+        if recent
+            .iter()
+            .filter_map(|e| e.position.as_ref()?.observed_at)
+            .any(|at| at == observed)
+        {
+            flags.push(IssAnomaly::DuplicateTimestamp);
+        }
+    }
+    if let Some(alt) = position.altitude {
+        if !(limits.min_altitude_km..=limits.max_altitude_km).contains(&alt) {
+            flags.push(IssAnomaly::AltitudeOutOfBand);
+        }
+    }
+    if let Some(vel) = position.velocity {
+        if !(limits.min_velocity_kmh..=limits.max_velocity_kmh).contains(&vel) {
+            flags.push(IssAnomaly::VelocityOutOfRange);
+        }
+    }
+
+    let previous = recent
+        .iter()
+        .find(|e| e.anomalies.is_empty())
+        .and_then(|e| Some((e, e.position.as_ref()?)));
+    if let Some((prev_entry, prev)) = previous {
+        let now = position.observed_at.unwrap_or(fetched_at);
+        let then = prev.observed_at.unwrap_or(prev_entry.fetched_at);
+        let dt_sec = seconds_between(then, now);
+        let dist = haversine_km(prev.latitude, prev.longitude, position.latitude, position.longitude);
+        if dt_sec > 0.0 && dist < 0.01 {
+            flags.push(IssAnomaly::FrozenPosition);
+        } else if dt_sec > 0.0 && dist / dt_sec * 3_600.0 > limits.max_ground_speed_kmh {
+            flags.push(IssAnomaly::Teleport);
+        }
+    }
+    flags
+}
+
+/// Distance between two providers' positions, allowing for the ISS's motion between
+/// their observation times (unknown times count as simultaneous).
+fn compare_positions(a: &IssPosition, b: &IssPosition, max_km: f64) -> IssCrossCheck {
+    let distance_km = haversine_km(a.latitude, a.longitude, b.latitude, b.longitude);
+    let dt_sec = match (a.observed_at, b.observed_at) {
+        (Some(x), Some(y)) => seconds_between(x, y).abs(),
+        _ => 0.0,
+    };
+    let allowed_km = max_km + MAX_GROUND_SPEED_KM_S * dt_sec;
+    IssCrossCheck {
+        provider: String::new(),
+        source_url: String::new(),
+        distance_km,
+        dt_sec,
+        allowed_km,
+        agrees: distance_km <= allowed_km,
+    }
+}
+
+/// Builds the trend over a chronologically ordered window of samples.
+/// The two-point fields describe the newest pair, the rest cover the whole window.
+fn compute_trend(rows: &[IssEntry]) -> IssTrend {
+    let points: Vec<IssTrackPoint> = rows.iter().filter_map(track_point).collect();
+    let mut trend = match rows {
+        [.., previous, newest] => compute_pair_trend(previous, newest),
+        _ => IssTrend::default(),
+    };
+    trend.samples = rows.len();
+
+    let gaps: Vec<f64> = rows
+        .windows(2)
+        .map(|pair| seconds_between(pair[0].fetched_at, pair[1].fetched_at))
+        .collect();
+    if !gaps.is_empty() {
+        trend.mean_gap_sec = Some(gaps.iter().sum::<f64>() / gaps.len() as f64);
+        trend.max_gap_sec = gaps.iter().copied().reduce(f64::max);
+    }
+    if let (Some(first), Some(last)) = (rows.first(), rows.last()) {
+        trend.window_sec = seconds_between(first.fetched_at, last.fetched_at);
+    }
+
+    trend.segments = points
+        .windows(2)
+        .map(|pair| {
+            let delta_km = haversine_km(pair[0].lat, pair[0].lon, pair[1].lat, pair[1].lon);
+            let dt_sec = seconds_between(pair[0].at, pair[1].at);
+            IssTrendSegment {
+                from_time: pair[0].at,
+                to_time: pair[1].at,
+                delta_km,
+                dt_sec,
+                ground_speed_kmh: (dt_sec > 0.0).then(|| delta_km / dt_sec * 3_600.0),
+            }
+        })
+        .collect();
+    trend.total_distance_km = trend.segments.iter().map(|seg| seg.delta_km).sum();
+
+    let velocities: Vec<f64> = points.iter().filter_map(|p| p.velocity).collect();
+    if !velocities.is_empty() {
+        trend.mean_velocity_kmh = Some(velocities.iter().sum::<f64>() / velocities.len() as f64);
+        trend.min_velocity_kmh = velocities.iter().copied().reduce(f64::min);
+        trend.max_velocity_kmh = velocities.iter().copied().reduce(f64::max);
+    }
+    trend.min_altitude_km = points.iter().filter_map(|p| p.altitude).reduce(f64::min);
+    trend.max_altitude_km = points.iter().filter_map(|p| p.altitude).reduce(f64::max);
+
+    trend.points = points;
+    trend
+}
+
+fn compute_pair_trend(previous: &IssEntry, newest: &IssEntry) -> IssTrend {
+    let lat1 = newest.position.as_ref().map(|p| p.latitude);
+    let lon1 = newest.position.as_ref().map(|p| p.longitude);
+    let lat0 = previous.position.as_ref().map(|p| p.latitude);
+    let lon0 = previous.position.as_ref().map(|p| p.longitude);
+    let velocity = newest.position.as_ref().and_then(|p| p.velocity);
 
     let (movement, delta_km) = if let (Some(a1), Some(o1), Some(a0), Some(o0)) = (lat1, lon1, lat0, lon0) {
         let dist = haversine_km(a0, o0, a1, o1);
@@ -384,7 +1530,7 @@ fn compute_trend(previous: &IssEntry, newest: &IssEntry) -> IssTrend {
     } else {
         (false, 0.0)
     };
-    let dt_sec = (newest.fetched_at - previous.fetched_at).num_milliseconds() as f64 / 1_000.0;
+    let dt_sec = seconds_between(previous.fetched_at, newest.fetched_at);
 
     IssTrend {
         movement,
@@ -397,17 +1543,500 @@ fn compute_trend(previous: &IssEntry, newest: &IssEntry) -> IssTrend {
         from_lon: lon0,
         to_lat: lat1,
         to_lon: lon1,
+        ..IssTrend::default()
     }
 }
 
+fn track_point(entry: &IssEntry) -> Option<IssTrackPoint> {
+    let position = entry.position.as_ref()?;
+    Some(IssTrackPoint {
+        at: entry.fetched_at,
+        lat: position.latitude,
+        lon: position.longitude,
+        altitude: position.altitude,
+        velocity: position.velocity,
+        propagated: false,
+    })
+}
+
+fn seconds_between(from: DateTime<Utc>, to: DateTime<Utc>) -> f64 {
+    (to - from).num_milliseconds() as f64 / 1_000.0
+}
+
+/// Reduces a time-ordered track to at most `max_points` samples.
+/// The first and last points are always kept; the interior is split into
+/// equal-size buckets and the middle sample of each bucket survives.
+fn downsample_track(points: Vec<IssTrackPoint>, max_points: usize) -> Vec<IssTrackPoint> {
+    if points.len() <= max_points || max_points < 2 {
+        return points;
+    }
+    let interior = points.len() - 2;
+    let buckets = max_points - 2;
+    let mut out = Vec::with_capacity(max_points);
+    out.push(points[0].clone());
+    for b in 0..buckets {
+        let start = 1 + b * interior / buckets;
+        let end = 1 + (b + 1) * interior / buckets;
+        out.push(points[(start + end) / 2].clone());
+    }
+    out.push(points[points.len() - 1].clone());
+    out
+}
+
 fn haversine_km(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    6371.0 * central_angle(lat1, lon1, lat2, lon2)
+}
+
+/// Angle in radians between two points on the sphere (haversine formula).
+fn central_angle(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
     let rlat1 = lat1.to_radians();
     let rlat2 = lat2.to_radians();
     let dlat = (lat2 - lat1).to_radians();
     let dlon = (lon2 - lon1).to_radians();
     let a = (dlat / 2.0).sin().powi(2) + rlat1.cos() * rlat2.cos() * (dlon / 2.0).sin().powi(2);
-    let c = 2.0 * a.sqrt().atan2((1.0 - a).sqrt());
-    6371.0 * c
+    2.0 * a.sqrt().atan2((1.0 - a).sqrt())
+}
+
+/// Point at `fraction` of the way along the great circle from (lat1, lon1) to (lat2, lon2).
+fn great_circle_point(lat1: f64, lon1: f64, lat2: f64, lon2: f64, fraction: f64) -> (f64, f64) {
+    let d = central_angle(lat1, lon1, lat2, lon2);
+    if d < 1e-12 {
+        return (lat1, lon1);
+    }
+    let a = ((1.0 - fraction) * d).sin() / d.sin();
+    let b = (fraction * d).sin() / d.sin();
+    let (p1, l1, p2, l2) = (lat1.to_radians(), lon1.to_radians(), lat2.to_radians(), lon2.to_radians());
+    let x = a * p1.cos() * l1.cos() + b * p2.cos() * l2.cos();
+    let y = a * p1.cos() * l1.sin() + b * p2.cos() * l2.sin();
+    let z = a * p1.sin() + b * p2.sin();
+    (z.atan2(x.hypot(y)).to_degrees(), y.atan2(x).to_degrees())
+}
+
+/// Interpolates between the samples bracketing `at`. Gaps above `max_gap` (or samples
+/// nearly antipodal, where the great circle is ambiguous) are returned untrusted.
+fn interpolate_between(
+    before: IssTrackPoint,
+    after: IssTrackPoint,
+    at: DateTime<Utc>,
+    max_gap: chrono::Duration,
+) -> IssInterpolatedPosition {
+    let gap_sec = seconds_between(before.at, after.at);
+    let fraction = if gap_sec > 0.0 {
+        (seconds_between(before.at, at) / gap_sec).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let (latitude, longitude) = great_circle_point(before.lat, before.lon, after.lat, after.lon, fraction);
+    let lerp = |x: Option<f64>, y: Option<f64>| x.zip(y).map(|(x, y)| x + (y - x) * fraction);
+    let angle = central_angle(before.lat, before.lon, after.lat, after.lon);
+    IssInterpolatedPosition {
+        at,
+        latitude,
+        longitude,
+        altitude: lerp(before.altitude, after.altitude),
+        velocity: lerp(before.velocity, after.velocity),
+        exact: before.at == at,
+        trusted: before.at == at
+            || (gap_sec <= max_gap.num_milliseconds() as f64 / 1_000.0 && angle < 170f64.to_radians()),
+        gap_sec,
+        fraction,
+        before,
+        after,
+    }
+}
+
+/// Drops buckets that saw too little of an orbit for a fair mean: fewer than half the
+/// median sample count.
+fn usable_buckets(mut buckets: Vec<AltitudeBucket>) -> Vec<AltitudeBucket> {
+    let mut counts: Vec<i64> = buckets.iter().map(|b| b.samples).collect();
+    counts.sort_unstable();
+    if let Some(&median) = counts.get(counts.len() / 2) {
+        buckets.retain(|b| b.samples * 2 >= median);
+    }
+    buckets
+}
+
+/// Least-squares line through `(x, y)` points as `(slope, intercept)`.
+fn linear_fit(points: &[(f64, f64)]) -> Option<(f64, f64)> {
+    if points.len() < 2 {
+        return None;
+    }
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+    let sxx: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+    if sxx <= f64::EPSILON {
+        return None;
+    }
+    let sxy: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+    let slope = sxy / sxx;
+    Some((slope, mean_y - slope * mean_x))
+}
+
+fn days_since(origin: DateTime<Utc>, at: DateTime<Utc>) -> f64 {
+    seconds_between(origin, at) / 86_400.0
+}
+
+/// Finds step changes in orbit-averaged altitude. Around each bucket, lines are fitted to
+/// the `span` buckets before and after it; when they disagree at the bucket by at least
+/// `min_km`, a burn happened there. Only the strongest bucket of each run of neighbouring
+/// candidates is reported, and windows stretched by data gaps are skipped.
+fn detect_altitude_steps(
+    norad_id: i32,
+    buckets: &[AltitudeBucket],
+    bucket_sec: i64,
+    span: usize,
+    min_km: f64,
+) -> Vec<OrbitEvent> {
+    let Some(origin) = buckets.first().map(|b| b.at) else {
+        return Vec::new();
+    };
+    let max_window_sec = (2 * span as i64 + 1) as f64 * bucket_sec as f64 * 1.5;
+    let fit = |range: &[AltitudeBucket]| {
+        let points: Vec<(f64, f64)> = range.iter().map(|b| (days_since(origin, b.at), b.mean_altitude)).collect();
+        linear_fit(&points)
+    };
+
+    let mut events: Vec<(usize, OrbitEvent)> = Vec::new();
+    for idx in span..buckets.len().saturating_sub(span) {
+        let before = &buckets[idx - span..idx];
+        let after = &buckets[idx + 1..=idx + span];
+        if seconds_between(before[0].at, after[span - 1].at) > max_window_sec {
+            continue;
+        }
+        let (Some((slope_before, icpt_before)), Some((slope_after, icpt_after))) = (fit(before), fit(after)) else {
+            continue;
+        };
+        let mid = buckets[idx].at + chrono::Duration::seconds(bucket_sec / 2);
+        let t = days_since(origin, mid);
+        let altitude_before = slope_before * t + icpt_before;
+        let altitude_after = slope_after * t + icpt_after;
+        let delta_km = altitude_after - altitude_before;
+        if delta_km.abs() < min_km {
+            continue;
+        }
+        let event = OrbitEvent {
+            id: 0,
+            norad_id,
+            kind: if delta_km > 0.0 { OrbitEventKind::Reboost } else { OrbitEventKind::Lowering },
+            at: mid,
+            altitude_before,
+            altitude_after,
+            delta_km,
+            decay_km_per_day: None,
+            detected_at: Utc::now(),
+        };
+        match events.last_mut() {
+            // Windows that still straddle the previous burn see it as an opposite step.
+            Some((last_idx, last)) if idx - *last_idx <= span && last.kind != event.kind => {}
+            Some((last_idx, last)) if idx - *last_idx <= span => {
+                if event.delta_km.abs() > last.delta_km.abs() {
+                    *last_idx = idx;
+                    *last = event;
+                }
+            }
+            _ => events.push((idx, event)),
+        }
+    }
+
+    // The decay before each burn comes from everything since the previous one, which is
+    // far less noisy than the short comparison window.
+    let mut since = 0;
+    events
+        .into_iter()
+        .map(|(idx, mut event)| {
+            let quiet = &buckets[since..idx];
+            let span_days = quiet.last().zip(quiet.first()).map_or(0.0, |(l, f)| days_since(f.at, l.at));
+            event.decay_km_per_day = if span_days >= 1.0 { fit(quiet).map(|(slope, _)| slope) } else { None };
+            since = idx + 1;
+            event
+        })
+        .collect()
+}
+
+/// Altitude trend (km/day) at each bucket from a least-squares fit over the trailing
+/// `window`. The window never reaches back past a burn in `resets`, and needs at least
+/// four buckets covering a quarter of the window.
+fn rolling_decay(buckets: &[AltitudeBucket], resets: &[DateTime<Utc>], window: chrono::Duration) -> Vec<Option<f64>> {
+    let Some(origin) = buckets.first().map(|b| b.at) else {
+        return Vec::new();
+    };
+    let min_span_days = window.num_seconds() as f64 / 86_400.0 / 4.0;
+    buckets
+        .iter()
+        .enumerate()
+        .map(|(idx, bucket)| {
+            let reset = resets.iter().filter(|r| **r <= bucket.at).max().copied();
+            let start = reset.map_or(bucket.at - window, |r| r.max(bucket.at - window));
+            let points: Vec<(f64, f64)> = buckets[..=idx]
+                .iter()
+                .filter(|b| b.at >= start)
+                .map(|b| (days_since(origin, b.at), b.mean_altitude))
+                .collect();
+            let span_days = points.last().zip(points.first()).map_or(0.0, |(last, first)| last.0 - first.0);
+            if points.len() < 4 || span_days < min_span_days {
+                return None;
+            }
+            linear_fit(&points).map(|(slope, _)| slope)
+        })
+        .collect()
+}
+
+/// Upper bound on the ground-track speed of a low-Earth-orbit satellite.
+const MAX_GROUND_SPEED_KM_S: f64 = 7.8;
+
+/// Latitude range and up to two longitude ranges (split at the antimeridian) enclosing a
+/// spherical cap; the unused range is empty (min > max). Caps reaching a pole span all
+/// longitudes.
+fn overflight_box(lat: f64, lon: f64, radius_km: f64) -> ((f64, f64), [(f64, f64); 2]) {
+    const ALL_LONGITUDES: [(f64, f64); 2] = [(-180.0, 180.0), (1.0, 0.0)];
+    let angle = radius_km / 6371.0;
+    let dlat = angle.to_degrees();
+    let lat_range = ((lat - dlat).max(-90.0), (lat + dlat).min(90.0));
+    if lat.abs() + dlat >= 90.0 {
+        return (lat_range, ALL_LONGITUDES);
+    }
+    let ratio = angle.sin() / lat.to_radians().cos();
+    if ratio >= 1.0 {
+        return (lat_range, ALL_LONGITUDES);
+    }
+    let dlon = ratio.asin().to_degrees();
+    let (lo, hi) = (lon - dlon, lon + dlon);
+    let lon_ranges = if lo < -180.0 {
+        [(lo + 360.0, 180.0), (-180.0, hi)]
+    } else if hi > 180.0 {
+        [(lo, 180.0), (-180.0, hi - 360.0)]
+    } else {
+        [(lo, hi), (1.0, 0.0)]
+    };
+    (lat_range, lon_ranges)
+}
+
+/// Where a great-circle segment enters and leaves a disc, as fractions of the segment.
+struct DiscCrossing {
+    enter: f64,
+    exit: f64,
+    closest: f64,
+    closest_km: f64,
+}
+
+fn disc_crossing(a: &IssTrackPoint, b: &IssTrackPoint, (lat, lon): (f64, f64), radius_km: f64) -> Option<DiscCrossing> {
+    let distance = |fraction: f64| {
+        let (p_lat, p_lon) = great_circle_point(a.lat, a.lon, b.lat, b.lon, fraction);
+        haversine_km(lat, lon, p_lat, p_lon)
+    };
+    // Distance to a fixed point is unimodal along a short arc, so a ternary search finds
+    // the closest approach; the ends are checked too for arcs moving away throughout.
+    let (mut lo, mut hi) = (0.0f64, 1.0f64);
+    for _ in 0..60 {
+        let m1 = lo + (hi - lo) / 3.0;
+        let m2 = hi - (hi - lo) / 3.0;
+        if distance(m1) <= distance(m2) {
+            hi = m2;
+        } else {
+            lo = m1;
+        }
+    }
+    let (closest, closest_km) = [(0.0, distance(0.0)), (1.0, distance(1.0)), ((lo + hi) / 2.0, distance((lo + hi) / 2.0))]
+        .into_iter()
+        .min_by(|x, y| x.1.total_cmp(&y.1))?;
+    if closest_km > radius_km {
+        return None;
+    }
+    let edge = |mut outside: f64, mut inside: f64| {
+        for _ in 0..40 {
+            let mid = (outside + inside) / 2.0;
+            if distance(mid) <= radius_km {
+                inside = mid;
+            } else {
+                outside = mid;
+            }
+        }
+        inside
+    };
+    Some(DiscCrossing {
+        enter: if distance(0.0) <= radius_km { 0.0 } else { edge(0.0, closest) },
+        exit: if distance(1.0) <= radius_km { 1.0 } else { edge(1.0, closest) },
+        closest,
+        closest_km,
+    })
+}
+
+/// Walks a time-ordered track and collects the intervals spent within `radius_km` of a
+/// location. `contiguous[i]` says whether `points[i]` directly follows `points[i - 1]` in
+/// the stored history; segments that are not contiguous or longer than `max_gap_sec` are
+/// treated as gaps, and an interval touching one is marked partial.
+fn find_overflights(
+    points: &[IssTrackPoint],
+    contiguous: &[bool],
+    target: (f64, f64),
+    radius_km: f64,
+    max_gap_sec: f64,
+) -> Vec<IssOverflight> {
+    let at_fraction = |a: &IssTrackPoint, b: &IssTrackPoint, fraction: f64| {
+        a.at + chrono::Duration::milliseconds(((b.at - a.at).num_milliseconds() as f64 * fraction).round() as i64)
+    };
+    let mut out = Vec::new();
+    let mut current: Option<IssOverflight> = None;
+    for (idx, point) in points.iter().enumerate() {
+        let prev = idx.checked_sub(1).map(|i| &points[i]);
+        match prev {
+            Some(prev) if contiguous[idx] && seconds_between(prev.at, point.at) <= max_gap_sec => {
+                if let Some(crossing) = disc_crossing(prev, point, target, radius_km) {
+                    let (closest_lat, closest_lon) =
+                        great_circle_point(prev.lat, prev.lon, point.lat, point.lon, crossing.closest);
+                    let closest_at = at_fraction(prev, point, crossing.closest);
+                    let pass = current.get_or_insert_with(|| IssOverflight {
+                        start: at_fraction(prev, point, crossing.enter),
+                        end: prev.at,
+                        duration_sec: 0.0,
+                        samples: 0,
+                        closest_km: crossing.closest_km,
+                        closest_at,
+                        closest_lat,
+                        closest_lon,
+                        partial: false,
+                    });
+                    if crossing.closest_km < pass.closest_km {
+                        pass.closest_km = crossing.closest_km;
+                        pass.closest_at = closest_at;
+                        pass.closest_lat = closest_lat;
+                        pass.closest_lon = closest_lon;
+                    }
+                    pass.end = at_fraction(prev, point, crossing.exit);
+                    if crossing.exit < 1.0 {
+                        out.extend(current.take());
+                    }
+                }
+            }
+            _ => {
+                if let Some(mut pass) = current.take() {
+                    pass.partial = true;
+                    out.push(pass);
+                }
+            }
+        }
+
+        let distance = haversine_km(target.0, target.1, point.lat, point.lon);
+        if distance <= radius_km {
+            let pass = current.get_or_insert(IssOverflight {
+                start: point.at,
+                end: point.at,
+                duration_sec: 0.0,
+                samples: 0,
+                closest_km: distance,
+                closest_at: point.at,
+                closest_lat: point.lat,
+                closest_lon: point.lon,
+                partial: true,
+            });
+            pass.samples += 1;
+        }
+    }
+    if let Some(mut pass) = current {
+        pass.partial = true;
+        out.push(pass);
+    }
+    for pass in &mut out {
+        pass.duration_sec = seconds_between(pass.start, pass.end);
+    }
+    out
+}
+
+/// Splits every sampled interval evenly between the regions at its two ends; intervals
+/// longer than `max_gap` are left unattributed rather than guessed.
+fn dwell_by_region(
+    points: &[IssTrackPoint],
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    max_gap: chrono::Duration,
+    lookup: impl Fn(f64, f64) -> GeoRegion,
+) -> IssRegionTime {
+    fn slot_of(region: &GeoRegion, items: &mut Vec<RegionDwell>) -> usize {
+        if let Some(idx) = items.iter().position(|item| item.region == *region) {
+            return idx;
+        }
+        items.push(RegionDwell {
+            region: region.clone(),
+            seconds: 0.0,
+            share: 0.0,
+            samples: 0,
+        });
+        items.len() - 1
+    }
+
+    let regions: Vec<GeoRegion> = points.iter().map(|p| lookup(p.lat, p.lon)).collect();
+    let mut items: Vec<RegionDwell> = Vec::new();
+    for region in &regions {
+        let idx = slot_of(region, &mut items);
+        items[idx].samples += 1;
+    }
+
+    let max_gap_sec = max_gap.num_milliseconds() as f64 / 1_000.0;
+    let mut covered_sec = 0.0;
+    for (pair, region_pair) in points.windows(2).zip(regions.windows(2)) {
+        let dt = seconds_between(pair[0].at, pair[1].at);
+        if dt <= 0.0 || dt > max_gap_sec {
+            continue;
+        }
+        covered_sec += dt;
+        for region in region_pair {
+            let idx = slot_of(region, &mut items);
+            items[idx].seconds += dt / 2.0;
+        }
+    }
+    for item in &mut items {
+        item.share = if covered_sec > 0.0 { item.seconds / covered_sec } else { 0.0 };
+    }
+    items.sort_by(|a, b| b.seconds.total_cmp(&a.seconds).then(b.samples.cmp(&a.samples)));
+
+    let window_sec = seconds_between(from, to).max(0.0);
+    IssRegionTime {
+        from,
+        to,
+        samples: points.len(),
+        max_gap_sec,
+        covered_sec,
+        uncovered_sec: (window_sec - covered_sec).max(0.0),
+        items,
+    }
+}
+
+fn validate_geofence(name: &str, shape: &GeofenceShape) -> Result<()> {
+    if name.trim().is_empty() {
+        return Err(anyhow!("geofence name must not be empty"));
+    }
+    let valid_coord = |lat: f64, lon: f64| (-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&lon);
+    match shape {
+        GeofenceShape::Circle { lat, lon, radius_km } => {
+            if !valid_coord(*lat, *lon) {
+                return Err(anyhow!("circle centre is out of range"));
+            }
+            if !(*radius_km > 0.0 && *radius_km < 20_000.0) {
+                return Err(anyhow!("radius_km must be between 0 and 20000"));
+            }
+        }
+        GeofenceShape::Polygon { points } => {
+            if points.len() < 3 {
+                return Err(anyhow!("polygon needs at least 3 points"));
+            }
+            if points.iter().any(|[lon, lat]| !valid_coord(*lat, *lon)) {
+                return Err(anyhow!("polygon points must be [lon, lat] within range"));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn geofence_contains(shape: &GeofenceShape, lat: f64, lon: f64) -> bool {
+    match shape {
+        GeofenceShape::Circle {
+            lat: clat,
+            lon: clon,
+            radius_km,
+        } => haversine_km(*clat, *clon, lat, lon) <= *radius_km,
+        GeofenceShape::Polygon { points } => geo::ring_contains(points, lat, lon),
+    }
 }
 
 fn last_days(days: i64) -> (String, String) {
@@ -431,31 +2060,170 @@ fn pick_string(value: &Value, keys: &[&str]) -> Option<String> {
     None
 }
 
-fn pick_datetime(value: &Value, keys: &[&str]) -> Option<DateTime<Utc>> {
-    for key in keys {
-        if let Some(v) = value.get(*key) {
-            if let Some(s) = v.as_str() {
-                if let Ok(dt) = s.parse::<DateTime<Utc>>() {
-                    return Some(dt);
-                }
-                if let Ok(ndt) = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S") {
-                    return Some(Utc.from_utc_datetime(&ndt));
-                }
-            } else if let Some(epoch) = v.as_i64() {
-                if let Some(dt) = Utc.timestamp_opt(epoch, 0).single() {
-                    return Some(dt);
-                }
-            }
-        }
-    }
-    None
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
     use serde_json::json;
 
+    #[test]
+    fn geofence_circle_and_polygon_containment() {
+        let moscow = GeofenceShape::Circle {
+            lat: 55.75,
+            lon: 37.62,
+            radius_km: 1_500.0,
+        };
+        assert!(geofence_contains(&moscow, 59.93, 30.31));
+        assert!(!geofence_contains(&moscow, 40.71, -74.0));
+
+        let square = GeofenceShape::Polygon {
+            points: vec![[0.0, 0.0], [10.0, 0.0], [10.0, 10.0], [0.0, 10.0]],
+        };
+        assert!(geofence_contains(&square, 5.0, 5.0));
+        assert!(!geofence_contains(&square, 5.0, 15.0));
+    }
+
+    #[test]
+    fn geofence_polygon_across_antimeridian() {
+        let pacific = GeofenceShape::Polygon {
+            points: vec![[170.0, -10.0], [-170.0, -10.0], [-170.0, 10.0], [170.0, 10.0], [170.0, -10.0]],
+        };
+        assert!(geofence_contains(&pacific, 0.0, 179.0));
+        assert!(geofence_contains(&pacific, 0.0, -175.0));
+        assert!(!geofence_contains(&pacific, 0.0, 0.0));
+    }
+
+    #[test]
+    fn dwell_by_region_splits_intervals_and_skips_gaps() {
+        use crate::domain::GeoRegionKind;
+        let at = |sec: i64| Utc.timestamp_opt(1_700_000_000 + sec, 0).unwrap();
+        let point = |sec: i64, lon: f64| IssTrackPoint {
+            at: at(sec),
+            lat: 0.0,
+            lon,
+            altitude: None,
+            velocity: None,
+            propagated: false,
+        };
+        let lookup = |_: f64, lon: f64| GeoRegion {
+            name: if lon < 0.0 { "West" } else { "East" }.to_string(),
+            code: None,
+            kind: GeoRegionKind::Country,
+        };
+        let points = vec![point(0, -2.0), point(60, -1.0), point(120, 1.0), point(2_000, 2.0)];
+        let report = dwell_by_region(&points, at(0), at(2_000), chrono::Duration::seconds(600), lookup);
+        assert_eq!(report.covered_sec, 120.0);
+        assert_eq!(report.uncovered_sec, 1_880.0);
+        assert_eq!(report.items[0].region.name, "West");
+        assert_eq!(report.items[0].seconds, 90.0);
+        assert_eq!(report.items[1].seconds, 30.0);
+        assert_eq!(report.items[1].samples, 2);
+    }
+
+    #[test]
+    fn interpolation_follows_great_circle_and_flags_gaps() {
+        let at = |sec: i64| Utc.timestamp_opt(1_700_000_000 + sec, 0).unwrap();
+        let point = |sec: i64, lat: f64, lon: f64, alt: f64| IssTrackPoint {
+            at: at(sec),
+            lat,
+            lon,
+            altitude: Some(alt),
+            velocity: None,
+            propagated: false,
+        };
+        let mid = interpolate_between(
+            point(0, 0.0, 170.0, 410.0),
+            point(120, 0.0, -170.0, 420.0),
+            at(60),
+            chrono::Duration::seconds(300),
+        );
+        assert!(mid.latitude.abs() < 1e-9);
+        assert!((mid.longitude.abs() - 180.0).abs() < 1e-9);
+        assert_eq!(mid.altitude, Some(415.0));
+        assert!(mid.trusted && !mid.exact);
+
+        let sparse = interpolate_between(
+            point(0, 10.0, 0.0, 410.0),
+            point(3_600, 20.0, 10.0, 410.0),
+            at(900),
+            chrono::Duration::seconds(300),
+        );
+        assert!(!sparse.trusted);
+        assert!((sparse.fraction - 0.25).abs() < 1e-12);
+    }
+
+    #[test]
+    fn overflights_interpolate_crossings_and_mark_gaps() {
+        let start = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        // Along the equator at 4° per minute, passing over (0, 0) at t = 300 s.
+        let points: Vec<IssTrackPoint> = (0..11)
+            .map(|i| IssTrackPoint {
+                at: start + chrono::Duration::seconds(i * 60),
+                lat: 0.0,
+                lon: -20.0 + i as f64 * 4.0,
+                altitude: Some(420.0),
+                velocity: None,
+                propagated: false,
+            })
+            .collect();
+        let mut contiguous = vec![true; points.len()];
+        contiguous[0] = false;
+
+        let passes = find_overflights(&points, &contiguous, (0.0, 0.0), 500.0, 300.0);
+        assert_eq!(passes.len(), 1);
+        let pass = &passes[0];
+        assert!(!pass.partial);
+        assert_eq!(pass.samples, 3);
+        assert!(pass.closest_km < 1e-6);
+        assert_eq!(pass.closest_at, start + chrono::Duration::seconds(300));
+        assert!((seconds_between(start, pass.start) - 232.5).abs() < 1.0);
+        assert!((pass.duration_sec - 135.0).abs() < 2.0);
+
+        contiguous[6] = false;
+        let split = find_overflights(&points, &contiguous, (0.0, 0.0), 500.0, 300.0);
+        assert_eq!(split.len(), 2);
+        assert!(split.iter().all(|pass| pass.partial));
+        assert_eq!(split[0].end, start + chrono::Duration::seconds(300));
+
+        let (_, lon_ranges) = overflight_box(0.0, 179.0, 500.0);
+        assert!(lon_ranges[0].0 > 170.0 && lon_ranges[0].1 == 180.0);
+        assert!(lon_ranges[1].0 == -180.0 && lon_ranges[1].1 < -170.0);
+    }
+
+    #[test]
+    fn altitude_steps_are_detected_and_reset_the_decay_trend() {
+        let start = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        // 0.05 km/day decay with orbit-to-orbit jitter and a 1.5 km reboost in bucket 30.
+        let buckets: Vec<AltitudeBucket> = (0..60)
+            .map(|i| {
+                let at = start + chrono::Duration::seconds(i * ORBIT_BUCKET_SEC);
+                let days = seconds_between(start, at) / 86_400.0;
+                let jitter = if i % 2 == 0 { 0.04 } else { -0.04 };
+                let step = if i > 30 { 1.5 } else if i == 30 { 0.7 } else { 0.0 };
+                let mean_altitude = 420.0 - 0.05 * days + jitter + step;
+                AltitudeBucket {
+                    at,
+                    samples: 45,
+                    mean_altitude,
+                    min_altitude: mean_altitude - 8.0,
+                    max_altitude: mean_altitude + 8.0,
+                }
+            })
+            .collect();
+
+        let events = detect_altitude_steps(ISS_NORAD_ID, &buckets, ORBIT_BUCKET_SEC, 8, 0.5);
+        assert_eq!(events.len(), 1, "{events:?}");
+        let burn = &events[0];
+        assert_eq!(burn.kind, OrbitEventKind::Reboost);
+        assert_eq!(burn.at, buckets[30].at + chrono::Duration::seconds(ORBIT_BUCKET_SEC / 2));
+        assert!((burn.delta_km - 1.5).abs() < 0.2, "delta {}", burn.delta_km);
+        assert!((burn.decay_km_per_day.unwrap() + 0.05).abs() < 0.05);
+
+        let decay = rolling_decay(&buckets, &[burn.at], chrono::Duration::hours(24));
+        assert!(decay[32].is_none());
+        let latest = decay.last().copied().flatten().unwrap();
+        assert!((latest + 0.05).abs() < 0.05, "decay {latest}");
+    }
+
     #[test]
     fn haversine_is_zero_for_same_point() {
         let dist = haversine_km(10.0, 20.0, 10.0, 20.0);
@@ -463,10 +2231,197 @@ mod tests {
     }
 
     #[test]
-    fn pick_datetime_reads_iso_string() {
-        let val = json!({ "ts": "2025-01-01T00:00:00Z" });
-        let dt = pick_datetime(&val, &["ts"]).unwrap();
-        assert_eq!(dt.year(), 2025);
+    fn rollup_buckets_truncate_to_utc_period_start() {
+        let at = Utc.with_ymd_and_hms(2024, 3, 10, 23, 59, 59).unwrap() + chrono::Duration::milliseconds(250);
+        assert_eq!(
+            RollupPeriod::Hourly.bucket_start(at),
+            Utc.with_ymd_and_hms(2024, 3, 10, 23, 0, 0).unwrap()
+        );
+        assert_eq!(RollupPeriod::Daily.bucket_start(at), Utc.with_ymd_and_hms(2024, 3, 10, 0, 0, 0).unwrap());
+        let boundary = Utc.with_ymd_and_hms(2024, 3, 11, 0, 0, 0).unwrap();
+        assert_eq!(RollupPeriod::Hourly.bucket_start(boundary), boundary);
+        assert_eq!(RollupPeriod::Daily.bucket_start(boundary), boundary);
+    }
+
+    #[test]
+    fn prune_cutoff_falls_on_the_day_start_retention_ago() {
+        let now = Utc.with_ymd_and_hms(2024, 3, 10, 15, 30, 0).unwrap();
+        assert_eq!(
+            prune_cutoff(now, chrono::Duration::days(30)),
+            Utc.with_ymd_and_hms(2024, 2, 9, 0, 0, 0).unwrap()
+        );
+        let midnight = Utc.with_ymd_and_hms(2024, 3, 10, 0, 0, 0).unwrap();
+        assert_eq!(
+            prune_cutoff(midnight, chrono::Duration::days(1)),
+            Utc.with_ymd_and_hms(2024, 3, 9, 0, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn osdr_removed_filter_parses_and_matches() {
+        let removed_at = Some(Utc.timestamp_opt(1_700_000_000, 0).unwrap());
+        let parse = OsdrRemovedFilter::parse;
+        assert_eq!(parse("exclude"), Some(OsdrRemovedFilter::Exclude));
+        assert_eq!(parse("include"), Some(OsdrRemovedFilter::Include));
+        assert_eq!(parse("only"), Some(OsdrRemovedFilter::Only));
+        assert_eq!(parse("Only"), None);
+        assert_eq!(parse(""), None);
+        assert!(OsdrRemovedFilter::default().matches(None));
+        assert!(!OsdrRemovedFilter::default().matches(removed_at));
+        assert!(OsdrRemovedFilter::Include.matches(None) && OsdrRemovedFilter::Include.matches(removed_at));
+        assert!(!OsdrRemovedFilter::Only.matches(None) && OsdrRemovedFilter::Only.matches(removed_at));
+    }
+
+    #[test]
+    fn osdr_sync_goes_full_when_asked_unsynced_or_due() {
+        let now = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let every = chrono::Duration::days(1);
+        let watermark = Some(now - chrono::Duration::hours(2));
+        let recent_full = Some(now - chrono::Duration::hours(3));
+        assert_eq!(sync_since(watermark, recent_full, false, now, every), watermark);
+        assert_eq!(sync_since(watermark, recent_full, true, now, every), None);
+        assert_eq!(sync_since(watermark, None, false, now, every), None);
+        assert_eq!(sync_since(watermark, Some(now - every), false, now, every), None);
+        assert_eq!(sync_since(None, recent_full, false, now, every), None);
+    }
+
+    #[test]
+    fn iss_position_parses_typed_fields_from_payload() {
+        let position = IssPosition::from_payload(&json!({
+            "latitude": 51.5,
+            "longitude": -0.12,
+            "altitude": 418.2,
+            "velocity": 27_580.4,
+            "visibility": "daylight",
+            "footprint": 4_510.0,
+            "timestamp": 1_700_000_000
+        }))
+        .unwrap();
+        assert_eq!((position.latitude, position.longitude), (51.5, -0.12));
+        assert_eq!(position.altitude, Some(418.2));
+        assert_eq!(position.visibility.as_deref(), Some("daylight"));
+        assert_eq!(position.observed_at, Utc.timestamp_opt(1_700_000_000, 0).single());
+
+        let sparse = IssPosition::from_payload(&json!({ "latitude": 1.0, "longitude": 2.0, "altitude": "high" }))
+            .unwrap();
+        assert_eq!(sparse.altitude, None);
+        assert_eq!(sparse.observed_at, None);
+        assert!(IssPosition::from_payload(&json!({ "latitude": "51.5", "longitude": -0.12 })).is_none());
+        assert!(IssPosition::from_payload(&json!({ "longitude": -0.12 })).is_none());
+    }
+
+    #[test]
+    fn downsample_track_keeps_endpoints_and_limit() {
+        let start = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let points: Vec<IssTrackPoint> = (0..1000)
+            .map(|i| IssTrackPoint {
+                at: start + chrono::Duration::seconds(i * 120),
+                lat: (i as f64 * 0.1).sin() * 51.6,
+                lon: i as f64 * 0.5 % 360.0 - 180.0,
+                altitude: Some(420.0),
+                velocity: Some(27_600.0),
+                propagated: false,
+            })
+            .collect();
+        let reduced = downsample_track(points.clone(), 100);
+        assert_eq!(reduced.len(), 100);
+        assert_eq!(reduced[0].at, points[0].at);
+        assert_eq!(reduced[99].at, points[999].at);
+        assert!(reduced.windows(2).all(|w| w[0].at < w[1].at));
+    }
+
+    #[test]
+    fn compute_trend_summarises_window() {
+        let start = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let rows: Vec<IssEntry> = [(0.0, 0.0, 27_500.0, 410.0), (0.0, 1.0, 27_600.0, 420.0), (0.0, 3.0, 27_700.0, 415.0)]
+            .iter()
+            .enumerate()
+            .map(|(i, (lat, lon, vel, alt))| {
+                let payload = json!({ "latitude": lat, "longitude": lon, "velocity": vel, "altitude": alt });
+                IssEntry {
+                    id: i as i64,
+                    norad_id: ISS_NORAD_ID,
+                    fetched_at: start + chrono::Duration::seconds(i as i64 * 60),
+                    source_url: String::new(),
+                    position: IssPosition::from_payload(&payload),
+                    payload,
+                    anomalies: Vec::new(),
+                }
+            })
+            .collect();
+        let trend = compute_trend(&rows);
+        assert_eq!(trend.samples, 3);
+        assert_eq!(trend.segments.len(), 2);
+        assert_eq!(trend.to_lon, Some(3.0));
+        assert_eq!(trend.from_lon, Some(1.0));
+        assert!((trend.total_distance_km - haversine_km(0.0, 0.0, 0.0, 3.0)).abs() < 1e-6);
+        assert_eq!(trend.min_velocity_kmh, Some(27_500.0));
+        assert_eq!(trend.max_altitude_km, Some(420.0));
+        assert_eq!(trend.max_gap_sec, Some(60.0));
+        assert_eq!(trend.window_sec, 120.0);
+    }
+
+    #[test]
+    fn find_passes_reports_ordered_horizon_crossings() {
+        let tle = Tle::parse(crate::orbit::fixtures::ISS_TLE).unwrap();
+        let sgp4 = Sgp4::from_tle(&tle).unwrap();
+        let observer = Observer {
+            lat: 55.75,
+            lon: 37.62,
+            alt_m: 150.0,
+        };
+        let passes = find_passes(&sgp4, &observer, sgp4.epoch, sgp4.epoch + chrono::Duration::days(2)).unwrap();
+        assert!(!passes.is_empty());
+        for pass in &passes {
+            assert!(pass.rise_time < pass.culmination_time && pass.culmination_time < pass.set_time);
+            assert!(pass.max_elevation > 0.0 && pass.max_elevation <= 90.0);
+            assert!(pass.duration_sec < 15.0 * 60.0);
+        }
+    }
+
+    #[test]
+    fn detect_anomalies_flags_frozen_and_teleporting_samples() {
+        let limits = AnomalyConfig {
+            min_altitude_km: 150.0,
+            max_altitude_km: 1_000.0,
+            min_velocity_kmh: 20_000.0,
+            max_velocity_kmh: 30_000.0,
+            max_ground_speed_kmh: 40_000.0,
+            cross_check_km: None,
+        };
+        let start = Utc.timestamp_opt(1_700_000_000, 0).unwrap();
+        let sample = |ts: i64, lat: f64, lon: f64, alt: f64| {
+            IssPosition::from_payload(&json!({
+                "latitude": lat, "longitude": lon, "altitude": alt, "velocity": 27_600.0, "timestamp": ts
+            }))
+            .unwrap()
+        };
+        let prev = sample(1_700_000_000, 10.0, 20.0, 420.0);
+        let recent = vec![IssEntry {
+            id: 1,
+            norad_id: ISS_NORAD_ID,
+            fetched_at: start,
+            source_url: String::new(),
+            payload: Value::Null,
+            position: Some(prev.clone()),
+            anomalies: Vec::new(),
+        }];
+
+        let ok = sample(1_700_000_060, 10.5, 23.0, 421.0);
+        assert!(detect_anomalies(Some(&ok), start, &recent, &limits).is_empty());
+
+        let frozen = sample(1_700_000_120, 10.0, 20.0, 420.0);
+        assert_eq!(detect_anomalies(Some(&frozen), start, &recent, &limits), vec![IssAnomaly::FrozenPosition]);
+
+        let teleport = sample(1_700_000_060, -40.0, 150.0, 2_000.0);
+        assert_eq!(
+            detect_anomalies(Some(&teleport), start, &recent, &limits),
+            vec![IssAnomaly::AltitudeOutOfBand, IssAnomaly::Teleport]
+        );
+
+        let duplicate = detect_anomalies(Some(&prev), start, &recent, &limits);
+        assert!(duplicate.contains(&IssAnomaly::DuplicateTimestamp));
+        assert_eq!(detect_anomalies(None, start, &recent, &limits), vec![IssAnomaly::MissingPosition]);
     }
 }
 
@@ -0,0 +1,144 @@
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+
+use crate::{
+    domain::{Sunlight, SunlightState},
+    orbit,
+};
+
+const SUN_RADIUS_KM: f64 = 696_000.0;
+const EARTH_RADIUS_KM: f64 = 6_378.137;
+
+/// Geocentric latitude/longitude of the point with the Sun at the zenith.
+pub fn subsolar_point(at: DateTime<Utc>) -> (f64, f64) {
+    let [x, y, z] = orbit::teme_to_ecef(orbit::sun_position_teme(at), at);
+    (z.atan2(x.hypot(y)).to_degrees(), orbit::normalize_lon(y.atan2(x).to_degrees()))
+}
+
+/// Elevation of the Sun's centre (degrees) seen from a ground point, ignoring refraction.
+pub fn sun_elevation(lat: f64, lon: f64, at: DateTime<Utc>) -> f64 {
+    let (sun_lat, sun_lon) = subsolar_point(at);
+    let (lat, sun_lat) = (lat.to_radians(), sun_lat.to_radians());
+    let hour_angle = (lon - sun_lon).to_radians();
+    (lat.sin() * sun_lat.sin() + lat.cos() * sun_lat.cos() * hour_angle.cos())
+        .clamp(-1.0, 1.0)
+        .asin()
+        .to_degrees()
+}
+
+/// Illumination of a satellite at a geodetic position. The Sun and Earth are treated as
+/// discs seen from the satellite; their overlap gives umbra, penumbra or full sunlight.
+pub fn sunlight(lat: f64, lon: f64, alt_km: f64, at: DateTime<Utc>) -> Sunlight {
+    let satellite = orbit::geodetic_to_ecef(lat, lon, alt_km);
+    let sun = orbit::teme_to_ecef(orbit::sun_position_teme(at), at);
+    let to_sun = [sun[0] - satellite[0], sun[1] - satellite[1], sun[2] - satellite[2]];
+    let to_earth = [-satellite[0], -satellite[1], -satellite[2]];
+
+    let sun_radius = (SUN_RADIUS_KM / orbit::norm(to_sun)).asin();
+    let earth_radius = (EARTH_RADIUS_KM / orbit::norm(to_earth)).min(1.0).asin();
+    let dot = to_sun.iter().zip(&to_earth).map(|(a, b)| a * b).sum::<f64>();
+    let separation = (dot / (orbit::norm(to_sun) * orbit::norm(to_earth))).clamp(-1.0, 1.0).acos();
+
+    let (state, sun_fraction) = if separation >= sun_radius + earth_radius {
+        (SunlightState::Sunlit, 1.0)
+    } else if separation <= earth_radius - sun_radius {
+        (SunlightState::Umbra, 0.0)
+    } else {
+        let hidden = disc_overlap(sun_radius, earth_radius, separation) / (std::f64::consts::PI * sun_radius * sun_radius);
+        (SunlightState::Penumbra, 1.0 - hidden)
+    };
+    Sunlight {
+        state,
+        sun_fraction: sun_fraction.clamp(0.0, 1.0),
+        ground_sun_elevation: sun_elevation(lat, lon, at),
+    }
+}
+
+/// Area shared by two discs of angular radii `a` and `b` whose centres are `c` apart
+/// (small-angle planar approximation).
+fn disc_overlap(a: f64, b: f64, c: f64) -> f64 {
+    let x = (c * c + a * a - b * b) / (2.0 * c);
+    let y = (a * a - x * x).max(0.0).sqrt();
+    a * a * (x / a).clamp(-1.0, 1.0).acos() + b * b * ((c - x) / b).clamp(-1.0, 1.0).acos() - c * y
+}
+
+/// Day/night boundary as `[lon, lat]` vertices from -180° to 180°, one every `step_deg`.
+pub fn terminator(at: DateTime<Utc>, step_deg: f64) -> Vec<[f64; 2]> {
+    let (sun_lat, sun_lon) = subsolar_point(at);
+    // At the equinoxes the terminator is a meridian pair; nudge the declination so the
+    // latitude formula stays defined.
+    let declination = if sun_lat.abs() < 1e-6 { 1e-6f64.copysign(sun_lat) } else { sun_lat }.to_radians();
+    let steps = (360.0 / step_deg).ceil() as usize;
+    (0..=steps)
+        .map(|i| {
+            let lon = (-180.0 + i as f64 * 360.0 / steps as f64).min(180.0);
+            let hour_angle = (lon - sun_lon).to_radians();
+            let lat = (-hour_angle.cos() / declination.tan()).atan().to_degrees();
+            [lon, lat]
+        })
+        .collect()
+}
+
+/// GeoJSON FeatureCollection with the night-side polygon, the terminator line and the
+/// subsolar point. The polygon closes over the pole that is in darkness.
+pub fn terminator_geojson(at: DateTime<Utc>, step_deg: f64) -> Value {
+    let (sun_lat, sun_lon) = subsolar_point(at);
+    let line = terminator(at, step_deg);
+    let dark_pole = if sun_lat >= 0.0 { -90.0 } else { 90.0 };
+    let mut night = line.clone();
+    night.push([180.0, dark_pole]);
+    night.push([-180.0, dark_pole]);
+    night.push(line[0]);
+
+    json!({
+        "type": "FeatureCollection",
+        "properties": { "at": at, "subsolar_lat": sun_lat, "subsolar_lon": sun_lon },
+        "features": [
+            {
+                "type": "Feature",
+                "geometry": { "type": "Polygon", "coordinates": [night] },
+                "properties": { "kind": "night" }
+            },
+            {
+                "type": "Feature",
+                "geometry": { "type": "LineString", "coordinates": line },
+                "properties": { "kind": "terminator" }
+            },
+            {
+                "type": "Feature",
+                "geometry": { "type": "Point", "coordinates": [sun_lon, sun_lat] },
+                "properties": { "kind": "subsolar" }
+            }
+        ]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn subsolar_point_and_shadow_follow_the_seasons() {
+        let solstice = Utc.with_ymd_and_hms(2024, 6, 20, 20, 51, 0).unwrap();
+        let (lat, _) = subsolar_point(solstice);
+        assert!((lat - 23.44).abs() < 0.05, "declination {lat}");
+
+        let equinox = Utc.with_ymd_and_hms(2024, 3, 20, 12, 0, 0).unwrap();
+        let (lat, lon) = subsolar_point(equinox);
+        assert!(lat.abs() < 0.5);
+        assert!(lon.abs() < 3.0, "noon subsolar longitude {lon}");
+
+        assert_eq!(sunlight(lat, lon, 420.0, equinox).state, SunlightState::Sunlit);
+        let midnight = sunlight(-lat, orbit::normalize_lon(lon + 180.0), 420.0, equinox);
+        assert_eq!(midnight.state, SunlightState::Umbra);
+        assert!(midnight.ground_sun_elevation < -80.0);
+
+        for [lon, lat] in terminator(solstice, 5.0) {
+            assert!(sun_elevation(lat, lon, solstice).abs() < 1e-6, "terminator at ({lat}, {lon})");
+        }
+        let geojson = terminator_geojson(solstice, 5.0);
+        let ring = geojson["features"][0]["geometry"]["coordinates"][0].as_array().unwrap();
+        assert_eq!(ring[ring.len() - 2], json!([-180.0, -90.0]));
+    }
+}